@@ -0,0 +1,22 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use polycubes::{generate_shapes_up_to_size, ShapeMinimal, ShapeWithGrid};
+
+fn generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generation");
+    group.sample_size(10);
+
+    for n in [4, 6, 8, 10] {
+        group.bench_with_input(BenchmarkId::new("A32", n), &n, |b, &n| {
+            b.iter(|| generate_shapes_up_to_size::<ShapeWithGrid, i32>(n))
+        });
+        group.bench_with_input(BenchmarkId::new("B8", n), &n, |b, &n| {
+            b.iter(|| generate_shapes_up_to_size::<ShapeMinimal, i8>(n))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, generation);
+criterion_main!(benches);
@@ -0,0 +1,37 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use polycubes::poly_2d::symmetry::{free_representative_uncached, symmetry_class_uncached, AnalyzedShape};
+use polycubes::{generate_shapes_up_to_size, ShapeWithGrid};
+
+// the symmetry class, multiplicity and free representative of every poly of size 10, once with each query rotating
+// the shape on its own, and once from the images AnalyzedShape computes up front
+fn symmetry(c: &mut Criterion) {
+    let mut group = c.benchmark_group("symmetry");
+    group.sample_size(10);
+
+    let polys = generate_shapes_up_to_size::<ShapeWithGrid, i32>(10).remove(&10).unwrap();
+    group.bench_function("uncached/10", |b| {
+        b.iter(|| {
+            polys
+                .iter()
+                .map(|poly| (symmetry_class_uncached(poly), poly.rotation_multiplicity(), free_representative_uncached(poly)))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("cached/10", |b| {
+        b.iter(|| {
+            polys
+                .iter()
+                .map(|poly| {
+                    let analyzed = AnalyzedShape::new(poly);
+                    (analyzed.symmetry_class(), analyzed.rotation_multiplicity(), analyzed.free_representative())
+                })
+                .collect::<Vec<_>>()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, symmetry);
+criterion_main!(benches);
@@ -1,21 +1,77 @@
+use std::error::Error;
 use std::fmt::Display;
+use std::ops::Range;
+use std::path::PathBuf;
 use std::str::FromStr;
 
-use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+
+use crate::poly_2d::symmetry::SymmetryClass;
+use crate::verbosity::Verbosity;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Print more progress, such as the configuration used. May be repeated
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    pub verbose: u8,
+
+    /// Print no progress, only results
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+}
+
+impl Cli {
+    pub fn verbosity(&self) -> Verbosity {
+        match (self.quiet, self.verbose) {
+            (true, _) => Verbosity::Quiet,
+            (false, 0) => Verbosity::Normal,
+            (false, _) => Verbosity::Verbose,
+        }
+    }
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Generates random walks (snakes) in 2 dimensions
-    Snake2d { n: usize },
+    Snake2d {
+        n: usize,
+
+        /// Grow this many walks, and print the longest and how many reached each length
+        #[arg(long, default_value_t = 1)]
+        count: usize,
+
+        /// Grow the walks on all threads
+        #[arg(long)]
+        parallel: bool,
+
+        /// Seed for the walks, random if not given
+        #[arg(long)]
+        seed: Option<u64>,
+    },
     /// Generates polycubes in 2 dimensions
     Poly2d(Poly2d),
+    /// Lists every connected set of n cells within a box of the given size, and the distinct polys among them
+    Box {
+        #[arg(value_parser = clap::value_parser!(u64).range(1..=64))]
+        width: u64,
+        #[arg(value_parser = clap::value_parser!(u64).range(1..=64))]
+        height: u64,
+        n: usize,
+    },
+    /// Generates polycubes in the given number of dimensions
+    Poly(Poly),
+    /// Reads one size per line from stdin, and prints the number of free, one-sided and fixed polys of each size
+    Repl,
+    /// Compares the polys of one size in two files written by poly2d with --output and --format compact, or with
+    /// --dump-bin
+    Diff(Diff),
+    /// Prints the canonical compact encoding and symmetry of each shape in a file of ascii art shapes, separated by
+    /// blank lines, or in a file written by poly2d with --dump-bin
+    Classify(Classify),
 }
 
 #[derive(Args, Debug)]
@@ -26,8 +82,202 @@ pub struct Poly2d {
     #[arg(short, long)]
     pub report_polys: bool,
 
-    #[arg(short, long)]
+    #[arg(short, long, ignore_case = true)]
     pub algorithm: Option<Algorithm>,
+
+    /// Only move polys to the origin, without canonicalizing their rotation, to show how many duplicates rotation
+    /// removes. Same as --algorithm fixed
+    #[arg(long, conflicts_with = "algorithm")]
+    pub no_canonicalize: bool,
+
+    /// Write a png per poly of the largest size into this directory (A32 only)
+    #[cfg(feature = "image")]
+    #[arg(long)]
+    pub png_dir: Option<PathBuf>,
+
+    /// Write all polys of the largest size into one png, laid out in a grid (A32 only)
+    #[cfg(feature = "image")]
+    #[arg(long)]
+    pub gallery: Option<PathBuf>,
+
+    /// Report how many polys of each size have each perimeter (A32 only)
+    #[arg(long)]
+    pub perimeter_histogram: bool,
+
+    /// Write generated polys to a file, grouped by size (A32 only)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Write generated polys to one file per size in this directory, named size-<n>.txt (A32 only)
+    #[arg(long)]
+    pub output_dir: Option<PathBuf>,
+
+    /// Also write manifest.json to the output directory, listing each file with the number of its polys and a hash of
+    /// them that doesn't depend on the format
+    #[arg(long, requires = "output_dir")]
+    pub manifest: bool,
+
+    /// Write generated polys to a compact binary file, which diff and classify can read (A32 only)
+    #[arg(long)]
+    pub dump_bin: Option<PathBuf>,
+
+    /// Format of reported and written polys
+    #[arg(short, long)]
+    pub format: Option<Format>,
+
+    /// Character for occupied cells of polys reported or written with --format ascii
+    #[arg(long, default_value_t = 'O')]
+    pub fill_char: char,
+
+    /// Character for empty cells of polys reported or written with --format ascii
+    #[arg(long, default_value_t = ' ')]
+    pub empty_char: char,
+
+    /// Draw polys with the last row first, so y grows upwards, in ascii output and pngs
+    #[arg(long)]
+    pub y_up: bool,
+
+    /// Report at most this many polys per size, narrowest first
+    #[arg(long, requires = "report_polys")]
+    pub limit: Option<usize>,
+
+    /// Report how many polys of each size have each symmetry (A32 only)
+    #[arg(long)]
+    pub symmetry_classes: bool,
+
+    /// Only generate polys that fit into a box this wide, in any rotation (A32 only)
+    #[arg(long)]
+    pub max_width: Option<usize>,
+
+    /// Only generate polys that fit into a box this high, in any rotation (A32 only)
+    #[arg(long)]
+    pub max_height: Option<usize>,
+
+    /// Report how many polys of each size have each bounding box, narrow side first (A32 only)
+    #[arg(long)]
+    pub bbox_histogram: bool,
+
+    /// Report the min, max and mean manhattan diameter and bounding box fill ratio of the polys of each size (A32 only)
+    #[arg(long)]
+    pub metrics: bool,
+
+    /// Only keep polys with at least the symmetries of this class, after generating all of them (A32 only)
+    #[arg(long, ignore_case = true)]
+    pub only_symmetry: Option<SymmetryClass>,
+
+    /// Report how many polys of each size have at least one hole (A32 only)
+    #[arg(long)]
+    pub with_holes: bool,
+
+    /// Print the canonical grid of each poly as it's hashed, with the rotation it was found in (A32 only)
+    #[arg(long)]
+    pub dump_canonical: bool,
+
+    /// Check that no two generated polys are rotations of each other, and that there are as many as Burnside's lemma
+    /// gives for the fixed polys of each size, and fail if not (A32 only)
+    #[arg(long)]
+    pub self_check: bool,
+
+    /// Also generate all polys with both A32 and B8, and fail if they find different numbers of any size
+    #[arg(long)]
+    pub compare_algorithms: bool,
+
+    /// Stop before the generated polys use more than this much memory, keeping all sizes completed so far (A32 only)
+    #[arg(long)]
+    pub max_memory_mb: Option<usize>,
+
+    /// Stop each size after finding this many polys, and only grow those further. Gives a sample, not all polys (A32 only)
+    #[arg(long)]
+    pub sample: Option<usize>,
+
+    /// Report how long growing each poly of the previous size took, as min, median and max per size (A32 only)
+    #[arg(long)]
+    pub profile: bool,
+
+    /// Report how many polys of each size grew into each number of distinct polys of the next size (A32 only)
+    #[arg(long)]
+    pub children_histogram: bool,
+
+    /// Grow the polys of each size in sorted order instead of hash order, to keep similar polys together (A32 only)
+    #[arg(long)]
+    pub sort_parents: bool,
+
+    /// Only grow polys into cells that have a single neighbor in them, unless the grown poly would have no such cell
+    /// at all. Every poly is still found, but far fewer duplicates are built and canonicalized (A32 only)
+    #[arg(long)]
+    pub append_origin_only: bool,
+
+    /// Only grow the polys of a size in parallel if there are at least this many of the size before, since for few
+    /// polys starting up the threads costs more than it saves. Defaults to 1000 (A32 only)
+    #[arg(long)]
+    pub parallel_threshold: Option<usize>,
+
+    /// Show progress as a single line that's updated in place, with the number of polys found so far. Falls back to a
+    /// line per size when output isn't a terminal
+    #[arg(long)]
+    pub status_line: bool,
+
+    /// Report the number of polys up to each size
+    #[arg(long)]
+    pub cumulative: bool,
+
+    /// Report the ratio of the number of polys of each size to the size before, the last of which estimates the
+    /// growth constant
+    #[arg(long)]
+    pub growth_ratio: bool,
+
+    /// Write the number of polys, points and polys tried, and the time taken per size to a csv file
+    #[arg(long)]
+    pub csv: Option<PathBuf>,
+
+    /// Count polys on a torus this big, given as WxH, whose edges wrap around. Only supports counting, and up to 64
+    /// cells
+    #[arg(long)]
+    pub torus: Option<TorusSize>,
+
+    /// Only grow the largest size from this shard of the size before, given as I/K for the I-th of K shards counting
+    /// from 1. The parents are split by their sorted order, so K runs with I from 1 to K find every poly between them
+    /// (A32 only)
+    #[arg(long)]
+    pub shard: Option<Shard>,
+
+    /// Lattice of the cells that make up polys. Hex only supports counting
+    #[arg(short, long)]
+    pub lattice: Option<LatticeType>,
+
+    /// Which cells count as neighbors on the square lattice. King only supports counting
+    #[arg(short, long)]
+    pub connectivity: Option<Connectivity>,
+
+    /// Only count polys, without keeping them around for reporting (A32 only)
+    #[arg(long, conflicts_with_all = ["report_polys", "output", "output_dir", "dump_bin", "symmetry_classes", "perimeter_histogram", "bbox_histogram", "metrics", "only_symmetry", "with_holes", "dump_canonical", "self_check", "max_width", "max_height", "max_memory_mb", "sample", "profile", "children_histogram", "sort_parents", "append_origin_only", "parallel_threshold", "shard"])]
+    pub count_only: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct Poly {
+    /// Number of dimensions of the polycubes. In 3 they're only counted, so flags that write or report polys only
+    /// apply in 2
+    #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u8).range(2..=3))]
+    pub dimensions: u8,
+
+    #[command(flatten)]
+    pub poly: Poly2d,
+}
+
+#[derive(Args, Debug)]
+pub struct Diff {
+    pub a: PathBuf,
+
+    pub b: PathBuf,
+
+    /// Size of the polys to compare
+    pub n: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct Classify {
+    pub input: PathBuf,
 }
 
 pub fn parse_cli() -> Cli {
@@ -35,19 +285,37 @@ pub fn parse_cli() -> Cli {
 }
 
 
-#[derive(Debug, ValueEnum, Clone)]
+#[derive(Debug, ValueEnum, Clone, PartialEq, Eq)]
 pub enum Algorithm {
+    /// Points and a canonical grid, with 32 bit coordinates
+    #[value(alias = "grid")]
     A32,
+    /// Points only, with 8 bit coordinates
+    #[value(alias = "minimal")]
     B8,
+    /// Like A32, but without canonicalizing rotations, so rotated polys count separately. Only supports counting
+    Fixed,
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownAlgorithm(pub String);
+
+impl Display for UnknownAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown algorithm '{}'", self.0)
+    }
+}
+
+impl Error for UnknownAlgorithm {}
+
 impl FromStr for Algorithm {
-    type Err = ();
+    type Err = UnknownAlgorithm;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
-            "a32" => Ok(Algorithm::A32),
-            "b8" => Ok(Algorithm::B8),
-            _ => Err(())
+            "a32" | "grid" => Ok(Algorithm::A32),
+            "b8" | "minimal" => Ok(Algorithm::B8),
+            "fixed" => Ok(Algorithm::Fixed),
+            _ => Err(UnknownAlgorithm(s.to_string()))
         }
     }
 }
@@ -56,7 +324,175 @@ impl Display for Algorithm {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", match self {
             Algorithm::A32 => "A32",
-            Algorithm::B8 => "B8"
+            Algorithm::B8 => "B8",
+            Algorithm::Fixed => "Fixed",
         })
     }
-}
\ No newline at end of file
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy)]
+pub enum Format {
+    /// One row of 'O's and spaces per line
+    Ascii,
+    /// Golly's run length encoding
+    Rle,
+    /// One line per poly, with its size and cells in hex
+    Compact,
+    /// One line per poly, with the x,y of each cell separated by spaces
+    Coords,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TorusSize {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl FromStr for TorusSize {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (width, height) = s.split_once('x').ok_or_else(|| format!("expected WxH, got '{}'", s))?;
+        let parse = |n: &str| n.parse::<usize>().map_err(|e| format!("invalid torus dimension '{}': {}", n, e));
+        let torus = TorusSize { width: parse(width)?, height: parse(height)? };
+        match torus.width.checked_mul(torus.height) {
+            Some(0) => Err("torus must have at least one cell".to_string()),
+            Some(1..=64) => Ok(torus),
+            Some(cells) => Err(format!("torus can have at most 64 cells, {} has {}", s, cells)),
+            None => Err(format!("torus can have at most 64 cells, {} has more than fit in a usize", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    // from 1 to count
+    pub index: usize,
+    pub count: usize,
+}
+
+impl Shard {
+    // the indices of the parents in this shard, out of len sorted parents. shards differ in size by at most one
+    pub fn range(&self, len: usize) -> Range<usize> {
+        len * (self.index - 1) / self.count..len * self.index / self.count
+    }
+}
+
+impl FromStr for Shard {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (index, count) = s.split_once('/').ok_or_else(|| format!("expected I/K, got '{}'", s))?;
+        let parse = |n: &str| n.parse::<usize>().map_err(|e| format!("invalid shard '{}': {}", n, e));
+        let shard = Shard { index: parse(index)?, count: parse(count)? };
+        if (1..=shard.count).contains(&shard.index) {
+            Ok(shard)
+        } else {
+            Err(format!("shard must be from 1 to {}, got {}", shard.count, shard.index))
+        }
+    }
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy)]
+pub enum LatticeType {
+    /// Squares, i.e. polyominoes
+    Square,
+    /// Hexagons, i.e. polyhexes
+    Hex,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy)]
+pub enum Connectivity {
+    /// Cells sharing an edge, i.e. polyominoes
+    Edge,
+    /// Cells sharing an edge or a corner, i.e. polyplets
+    King,
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use clap::Parser;
+
+    use crate::cli::{Algorithm, Cli, Commands, Shard, TorusSize, UnknownAlgorithm};
+    use crate::poly_2d::config::GenerationConfig;
+    use crate::verbosity::Verbosity;
+
+    fn parse_algorithm(spelling: &str) -> Algorithm {
+        let cli = Cli::try_parse_from(["polycubes", "poly2d", "3", "--algorithm", spelling]).unwrap();
+        match cli.command {
+            Commands::Poly2d(poly2d) => poly2d.algorithm.unwrap(),
+            _ => panic!("expected poly2d"),
+        }
+    }
+
+    #[test]
+    fn should_parse_algorithm_spellings() {
+        for (spellings, expected) in [
+            (["a32", "A32", "grid", "Grid"], Algorithm::A32),
+            (["b8", "B8", "minimal", "MINIMAL"], Algorithm::B8),
+            (["fixed", "Fixed", "FIXED", "fIxEd"], Algorithm::Fixed),
+        ] {
+            for spelling in spellings {
+                assert_eq!(parse_algorithm(spelling), expected, "{}", spelling);
+                assert_eq!(Algorithm::from_str(spelling), Ok(expected.clone()), "{}", spelling);
+            }
+        }
+    }
+
+    #[test]
+    fn should_parse_verbosity() {
+        let verbosity = |args: &[&str]| Cli::try_parse_from(["polycubes"].iter().chain(args)).unwrap().verbosity();
+        assert_eq!(verbosity(&["poly2d", "3"]), Verbosity::Normal);
+        assert_eq!(verbosity(&["-q", "poly2d", "3"]), Verbosity::Quiet);
+        assert_eq!(verbosity(&["poly2d", "3", "--quiet"]), Verbosity::Quiet);
+        assert_eq!(verbosity(&["-v", "poly2d", "3"]), Verbosity::Verbose);
+        assert_eq!(verbosity(&["poly2d", "-vv", "3"]), Verbosity::Verbose);
+        assert!(Cli::try_parse_from(["polycubes", "-q", "-v", "poly2d", "3"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_shard() {
+        assert_eq!(Shard::from_str("1/3"), Ok(Shard { index: 1, count: 3 }));
+        assert_eq!(Shard::from_str("3/3"), Ok(Shard { index: 3, count: 3 }));
+        for invalid in ["0/3", "4/3", "1/0", "3", "a/3"] {
+            assert!(Shard::from_str(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn should_split_into_adjacent_shards() {
+        let ranges: Vec<_> = (1..=3).map(|index| Shard { index, count: 3 }.range(10)).collect();
+        assert_eq!(ranges, [0..3, 3..6, 6..10]);
+        assert_eq!(Shard { index: 2, count: 3 }.range(1), 0..0);
+    }
+
+    #[test]
+    fn should_parse_torus_size() {
+        assert_eq!(TorusSize::from_str("3x4"), Ok(TorusSize { width: 3, height: 4 }));
+        assert_eq!(TorusSize::from_str("8x8"), Ok(TorusSize { width: 8, height: 8 }));
+        assert!(TorusSize::from_str("3").is_err());
+        assert!(TorusSize::from_str("ax3").is_err());
+        // the number of cells overflows, and must not wrap around to something small
+        assert!(TorusSize::from_str("4294967296x4294967296").unwrap_err().contains("at most 64 cells"));
+        assert!(TorusSize::from_str("0x3").is_err());
+        assert!(TorusSize::from_str("9x8").is_err());
+        assert!(Cli::try_parse_from(["polycubes", "poly2d", "3", "--torus", "3x3"]).is_ok());
+    }
+
+    #[test]
+    fn should_not_canonicalize_with_fixed_algorithm() {
+        let config = |args: &[&str]| match Cli::try_parse_from(["polycubes", "poly2d", "3"].iter().chain(args)).unwrap().command {
+            Commands::Poly2d(poly2d) => GenerationConfig::from(poly2d),
+            _ => panic!("expected poly2d"),
+        };
+        assert_eq!(config(&["--no-canonicalize"]).algorithm, Algorithm::Fixed);
+        assert_eq!(config(&[]).algorithm, Algorithm::A32);
+        assert!(Cli::try_parse_from(["polycubes", "poly2d", "3", "--no-canonicalize", "-a", "b8"]).is_err());
+    }
+
+    #[test]
+    fn should_reject_unknown_algorithm() {
+        assert!(Cli::try_parse_from(["polycubes", "poly2d", "3", "--algorithm", "c16"]).is_err());
+        assert_eq!(Algorithm::from_str("c16"), Err(UnknownAlgorithm("c16".to_string())));
+    }
+}
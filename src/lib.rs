@@ -1,15 +1,162 @@
+//! Generates polyominoes (polycubes in 2 dimensions) by growing each size from the previous one.
+//!
+//! ```
+//! use nalgebra::Vector2;
+//! use polycubes::{ShapeN, ShapeWithGrid};
+//!
+//! // an L tetromino, and the same rotated by 90 degrees
+//! let l = ShapeWithGrid::new(vec![Vector2::new(0, 0), Vector2::new(0, 1), Vector2::new(0, 2), Vector2::new(1, 0)]);
+//! let l_rotated = ShapeWithGrid::new(vec![Vector2::new(2, 1), Vector2::new(0, 0), Vector2::new(2, 0), Vector2::new(1, 0)]);
+//! assert_eq!(l, l_rotated);
+//! assert_eq!(polycubes::generate_shapes_exactly::<ShapeWithGrid, i32>(4).len(), 7);
+//! ```
+
+use std::error::Error;
+use std::io;
+
 mod cli;
-mod poly_2d;
+pub mod poly_2d;
+mod status_line;
+mod verbosity;
 
 use cli::parse_cli;
-use poly_2d::poly::generate_polys;
+use poly_2d::classify::classify_polys;
+use poly_2d::diff::diff_polys;
+use poly_2d::in_box::enumerate_polys_in_box;
+use poly_2d::repl::run_repl;
 use poly_2d::snake::generate_snake_2d;
+use verbosity::set_verbosity;
 
-pub fn generate_polycubes() {
-    let cli = parse_cli();
+pub use cli::{Algorithm, Connectivity, Format, LatticeType, Shard, TorusSize};
+pub use poly_2d::config::GenerationConfig;
+pub use poly_2d::poly::{
+    continue_generation, generate_extensions, generate_polycubes_3d, generate_polys, generate_sampled_shapes_up_to_size,
+    generate_sampled_shapes_up_to_size_sequential, generate_shapes_exactly, generate_shapes_up_to_size, generate_shapes_up_to_size_sequential,
+    generate_shapes_up_to_size_with_stats, generate_shapes_with_size, generate_shard_with_size, merge_sets, total_count,
+    ExpansionProfile, GenerationStats,
+};
+pub use poly_2d::poly_error::PolyError;
+pub use poly_2d::shape::bounding_box_two_points::BoundingBoxTwoPoints;
+pub use poly_2d::shape::shape_error::{ParseError, ShapeError};
+pub use poly_2d::shape::shape_generic::ShapeN;
+pub use poly_2d::shape::shape_minimal::ShapeMinimal;
+pub use poly_2d::shape::shape_with_grid::{points_from_grid, ShapeWithGrid};
+pub use poly_2d::shape::weighted_shape::WeightedShape;
+pub use poly_2d::symmetry::{to_free, SymmetryGroup};
+pub use verbosity::Verbosity;
+
+pub fn generate_polycubes() -> Result<(), Box<dyn Error>> {
+    run(parse_cli())
+}
 
+fn run(cli: cli::Cli) -> Result<(), Box<dyn Error>> {
+    set_verbosity(cli.verbosity());
     match cli.command {
-        cli::Commands::Snake2d { n } => generate_snake_2d(n),
-        cli::Commands::Poly2d(poly2d) => generate_polys(poly2d),
+        cli::Commands::Snake2d { n, count, parallel, seed } => generate_snake_2d(n, count, parallel, seed)?,
+        cli::Commands::Poly2d(poly2d) => {
+            generate_polys(poly2d.into())?;
+        }
+        cli::Commands::Box { width, height, n } => enumerate_polys_in_box(width as usize, height as usize, n)?,
+        cli::Commands::Poly(poly) => {
+            generate_polys_in_dimensions(poly)?;
+        }
+        cli::Commands::Repl => run_repl(io::stdin().lock(), &mut io::stdout().lock())?,
+        cli::Commands::Diff(diff) => diff_polys(diff)?,
+        cli::Commands::Classify(classify) => classify_polys(classify)?,
+    }
+    Ok(())
+}
+
+// the shapes of each dimension have their own generator, so this picks the one to run
+fn generate_polys_in_dimensions(poly: cli::Poly) -> Result<Vec<GenerationStats>, PolyError> {
+    match poly.dimensions {
+        2 => generate_polys(poly.poly.into()),
+        3 => generate_polycubes_3d(poly.poly.into()),
+        d => Err(PolyError::UnsupportedDimensions(d)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use clap::Parser;
+
+    use crate::cli::{Cli, Commands};
+    use crate::{generate_polys, generate_polys_in_dimensions, run, GenerationConfig, GenerationStats};
+
+    fn run_with(args: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+        run(Cli::try_parse_from(["polycubes"].iter().chain(args)).unwrap())
+    }
+
+    #[test]
+    fn should_fail_on_invalid_configuration() {
+        let e = run_with(&["poly2d", "3", "--lattice", "hex", "--connectivity", "king"]).unwrap_err();
+        assert_eq!(e.to_string(), "king connectivity is only supported on the square lattice");
+        assert!(run_with(&["snake2d", "0"]).is_err());
+    }
+
+    #[test]
+    fn should_reject_a32_only_flags_for_other_runs() {
+        for args in [
+            &["poly2d", "3", "--lattice", "hex", "--output", "/tmp/polys.txt"][..],
+            &["poly2d", "3", "-c", "king", "--report-polys"],
+            &["poly2d", "3", "--torus", "3x3", "--dump-bin", "/tmp/polys.bin"],
+            &["poly2d", "3", "--algorithm", "b8", "--symmetry-classes"],
+            &["poly2d", "3", "--algorithm", "fixed", "--output-dir", "/tmp/polys", "--manifest"],
+            &["poly2d", "3", "--algorithm", "b8", "--shard", "1/2"],
+            &["poly2d", "3", "-l", "hex", "--parallel-threshold", "10"],
+        ] {
+            let e = run_with(args).unwrap_err();
+            assert!(e.to_string().contains("is only supported by algorithm A32"), "{:?}: {}", args, e);
+        }
+        // count_only conflicts with these in the cli, but not when configured directly
+        let config = GenerationConfig { max_n: 3, count_only: true, metrics: true, ..Default::default() };
+        assert_eq!(generate_polys(config).unwrap_err().to_string().split(' ').next(), Some("--metrics"));
+        assert!(run_with(&["poly2d", "3", "--shard", "1/2", "--parallel-threshold", "10"]).is_ok());
+    }
+
+    #[test]
+    fn should_fail_on_unwritable_output() {
+        let e = run_with(&["poly2d", "2", "--output", "/nonexistent/polys.txt"]).unwrap_err();
+        assert!(e.to_string().starts_with("could not write to /nonexistent/polys.txt"), "{}", e);
+    }
+
+    fn found(stats: Vec<GenerationStats>) -> Vec<usize> {
+        stats.iter().map(|s| s.found).collect()
+    }
+
+    #[test]
+    fn should_generate_same_polys_in_2_dimensions_as_poly2d() {
+        let parse = |args: &[&str]| Cli::try_parse_from(["polycubes", "-q"].iter().chain(args)).unwrap().command;
+        let (Commands::Poly(poly), Commands::Poly2d(poly2d)) =
+            (parse(&["poly", "--dimensions", "2", "7"]), parse(&["poly2d", "7"]))
+        else {
+            panic!("expected poly and poly2d");
+        };
+        assert_eq!(found(generate_polys_in_dimensions(poly).unwrap()), found(generate_polys(poly2d.into()).unwrap()));
+        assert!(Cli::try_parse_from(["polycubes", "poly", "--dimensions", "4", "3"]).is_err());
+    }
+
+    #[test]
+    fn should_generate_polycubes_in_3_dimensions() {
+        let parse = |args: &[&str]| match Cli::try_parse_from(["polycubes", "-q"].iter().chain(args)).unwrap().command {
+            Commands::Poly(poly) => poly,
+            _ => panic!("expected poly"),
+        };
+        // https://oeis.org/A000162
+        let stats = generate_polys_in_dimensions(parse(&["poly", "--dimensions", "3", "6"])).unwrap();
+        assert_eq!(found(stats), [1, 1, 2, 8, 29, 166]);
+
+        let e = run_with(&["poly", "--dimensions", "3", "3", "--lattice", "hex"]).unwrap_err();
+        assert_eq!(e.to_string(), "--lattice is only supported in 2 dimensions");
+        assert!(run_with(&["poly", "--dimensions", "3", "3", "--output", "/tmp/polycubes.txt"]).is_err());
+        let mut poly = parse(&["poly", "--dimensions", "3", "3"]);
+        poly.dimensions = 4;
+        let e = generate_polys_in_dimensions(poly).unwrap_err();
+        assert_eq!(e.to_string(), "4 dimensional polycubes are not implemented yet");
+    }
+
+    #[test]
+    fn should_succeed_on_valid_configuration() {
+        assert!(run_with(&["poly2d", "3"]).is_ok());
     }
 }
@@ -1,5 +1,13 @@
+use std::process::ExitCode;
+
 use polycubes::generate_polycubes;
 
-fn main() {
-    generate_polycubes();
+fn main() -> ExitCode {
+    match generate_polycubes() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,147 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::poly_2d::shape::shape_with_grid::ShapeWithGrid;
+
+// how many polys have each perimeter
+pub fn perimeter_histogram(polys: &HashSet<ShapeWithGrid>) -> BTreeMap<usize, usize> {
+    let mut histogram = BTreeMap::new();
+    for poly in polys {
+        *histogram.entry(poly.perimeter()).or_insert(0) += 1;
+    }
+    histogram
+}
+
+pub fn report_perimeter_histogram(max_n: usize, known_polys: &HashMap<usize, HashSet<ShapeWithGrid>>) {
+    println!("{: >4} {: >10} {: >10}", "n", "perimeter", "count");
+    for n in 1..=max_n {
+        for (perimeter, count) in perimeter_histogram(&known_polys[&n]) {
+            println!("{: >4} {: >10} {: >10}", n, perimeter, count);
+        }
+    }
+}
+
+// how many polys have each bounding box, as (width, height) with width <= height, since rotating a poly swaps them
+pub fn bbox_histogram(polys: &HashSet<ShapeWithGrid>) -> BTreeMap<(usize, usize), usize> {
+    let mut histogram = BTreeMap::new();
+    for poly in polys {
+        let (width, height) = (poly.width(), poly.height());
+        *histogram.entry((width.min(height), width.max(height))).or_insert(0) += 1;
+    }
+    histogram
+}
+
+pub fn report_bbox_histogram(max_n: usize, known_polys: &HashMap<usize, HashSet<ShapeWithGrid>>) {
+    println!("{: >4} {: >10} {: >10} {: >10}", "n", "width", "height", "count");
+    for n in 1..=max_n {
+        for ((width, height), count) in bbox_histogram(&known_polys[&n]) {
+            println!("{: >4} {: >10} {: >10} {: >10}", n, width, height, count);
+        }
+    }
+}
+
+// min, max and mean of some metric over a set of polys
+#[derive(Debug, PartialEq)]
+pub struct MetricSummary {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+
+impl MetricSummary {
+    // all zeros if there are no polys
+    pub fn of(polys: &HashSet<ShapeWithGrid>, metric: impl Fn(&ShapeWithGrid) -> f64) -> MetricSummary {
+        if polys.is_empty() {
+            return MetricSummary { min: 0.0, max: 0.0, mean: 0.0 };
+        }
+        let values: Vec<f64> = polys.iter().map(metric).collect();
+        MetricSummary {
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+        }
+    }
+}
+
+pub fn report_metrics(max_n: usize, known_polys: &HashMap<usize, HashSet<ShapeWithGrid>>) {
+    println!(
+        "{: >4} {: >10} {: >10} {: >10} {: >10} {: >10} {: >10}",
+        "n", "diam min", "diam max", "diam mean", "fill min", "fill max", "fill mean"
+    );
+    for n in 1..=max_n {
+        let diameter = MetricSummary::of(&known_polys[&n], |poly| poly.diameter() as f64);
+        let fill = MetricSummary::of(&known_polys[&n], |poly| poly.fill_ratio());
+        println!(
+            "{: >4} {: >10} {: >10} {: >10.3} {: >10.3} {: >10.3} {: >10.3}",
+            n, diameter.min, diameter.max, diameter.mean, fill.min, fill.max, fill.mean
+        );
+    }
+}
+
+pub fn report_holes(max_n: usize, known_polys: &HashMap<usize, HashSet<ShapeWithGrid>>) {
+    println!("{: >4} {: >10}", "n", "with holes");
+    for n in 1..=max_n {
+        println!("{: >4} {: >10}", n, count_with_holes(&known_polys[&n]));
+    }
+}
+
+pub fn count_with_holes(polys: &HashSet<ShapeWithGrid>) -> usize {
+    polys.iter().filter(|poly| poly.has_hole()).count()
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use crate::poly_2d::analysis::{bbox_histogram, count_with_holes, perimeter_histogram, MetricSummary};
+    use crate::poly_2d::poly::generate_shapes_up_to_size_sequential;
+    use crate::poly_2d::shape::shape_with_grid::ShapeWithGrid;
+
+    #[test]
+    fn should_tally_tetromino_perimeters() {
+        let known_polys = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(4);
+        // only the square is more compact than the rest
+        assert_eq!(perimeter_histogram(&known_polys[&4]), BTreeMap::from([(8, 1), (10, 6)]));
+        assert_eq!(perimeter_histogram(&known_polys[&1]), BTreeMap::from([(4, 1)]));
+    }
+
+    #[test]
+    fn should_tally_tetromino_bounding_boxes() {
+        let known_polys = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(4);
+        // I, then O, then T, S, Z, L, J
+        assert_eq!(bbox_histogram(&known_polys[&4]), BTreeMap::from([((1, 4), 1), ((2, 2), 1), ((2, 3), 5)]));
+    }
+
+    #[test]
+    fn should_sum_bounding_boxes_to_poly_count() {
+        let known_polys = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(8);
+        for n in 1..=8 {
+            let histogram = bbox_histogram(&known_polys[&n]);
+            assert_eq!(histogram.values().sum::<usize>(), known_polys[&n].len(), "n={}", n);
+            assert!(histogram.keys().all(|(width, height)| width <= height), "n={}", n);
+        }
+    }
+
+    #[test]
+    fn should_summarize_tetromino_metrics() {
+        let known_polys = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(4);
+        // O and T are 2 across, I, S, Z, L and J are 3
+        let diameter = MetricSummary::of(&known_polys[&4], |poly| poly.diameter() as f64);
+        assert_eq!(diameter, MetricSummary { min: 2.0, max: 3.0, mean: 19.0 / 7.0 });
+        // I and O fill their box, the other 5 cover 4 of 6 cells
+        let fill = MetricSummary::of(&known_polys[&4], |poly| poly.fill_ratio());
+        assert_eq!((fill.min, fill.max), (4.0 / 6.0, 1.0));
+        assert!((fill.mean - (2.0 + 5.0 * 4.0 / 6.0) / 7.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn should_count_polys_with_holes() {
+        let known_polys = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(8);
+        for n in 1..=6 {
+            assert_eq!(count_with_holes(&known_polys[&n]), 0, "n={}", n);
+        }
+        // the 3x3 ring with a corner missing, which is its own mirror image
+        assert_eq!(count_with_holes(&known_polys[&7]), 1);
+        // 6 free octominoes have a hole, 5 of which are chiral
+        assert_eq!(count_with_holes(&known_polys[&8]), 11);
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::Path;
+
+use crate::poly_2d::shape::shape_with_grid::ShapeWithGrid;
+
+// a binary dump of generated polys, smaller than any text format. all numbers are little endian
+//
+// header: MAGIC, version u8, dimension u8, symmetry u8, max_n u32
+// then for each size from 1 to max_n: count u64, and count polys of width u16, height u16, ShapeWithGrid::to_packed
+pub const MAGIC: &[u8; 4] = b"PLYB";
+const VERSION: u8 = 1;
+const DIMENSION: u8 = 2;
+// polys are canonical under rotations, but not reflections
+const SYMMETRY_ROTATIONS: u8 = 1;
+
+pub fn write_bin_to_file(path: &Path, max_n: usize, known_polys: &HashMap<usize, HashSet<ShapeWithGrid>>) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_bin(&mut writer, max_n, known_polys)?;
+    // dropping a BufWriter swallows errors, so flush explicitly
+    writer.flush()
+}
+
+pub fn write_bin(
+    out: &mut impl Write,
+    max_n: usize,
+    known_polys: &HashMap<usize, HashSet<ShapeWithGrid>>,
+) -> io::Result<()> {
+    out.write_all(MAGIC)?;
+    out.write_all(&[VERSION, DIMENSION, SYMMETRY_ROTATIONS])?;
+    out.write_all(&(max_n as u32).to_le_bytes())?;
+    for n in 1..=max_n {
+        // sorted, so that output is the same on every run
+        let mut polys: Vec<&ShapeWithGrid> = known_polys[&n].iter().collect();
+        polys.sort();
+        out.write_all(&(polys.len() as u64).to_le_bytes())?;
+        for poly in polys {
+            out.write_all(&(poly.width() as u16).to_le_bytes())?;
+            out.write_all(&(poly.height() as u16).to_le_bytes())?;
+            out.write_all(&poly.to_packed())?;
+        }
+    }
+    Ok(())
+}
+
+// whether the file starts like one written by write_bin, so readers of text formats can tell the two apart
+pub fn is_bin_file(path: &Path) -> io::Result<bool> {
+    let mut magic = [0; MAGIC.len()];
+    match File::open(path)?.read_exact(&mut magic) {
+        Ok(()) => Ok(&magic == MAGIC),
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn read_bin_from_file(path: &Path) -> io::Result<HashMap<usize, HashSet<ShapeWithGrid>>> {
+    read_bin(&mut BufReader::new(File::open(path)?))
+}
+
+// all sizes written by write_bin. anything that doesn't match the format is InvalidData
+pub fn read_bin(input: &mut impl Read) -> io::Result<HashMap<usize, HashSet<ShapeWithGrid>>> {
+    let mut magic = [0; MAGIC.len()];
+    input.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid("not a binary poly dump"));
+    }
+    let [version, dimension, symmetry] = read_array(input)?;
+    if version != VERSION {
+        return Err(invalid(&format!("unsupported version {}", version)));
+    }
+    if dimension != DIMENSION || symmetry != SYMMETRY_ROTATIONS {
+        return Err(invalid(&format!("unsupported dimension {} or symmetry {}", dimension, symmetry)));
+    }
+    let max_n = u32::from_le_bytes(read_array(input)?) as usize;
+
+    // not trusting max_n or count for the capacity, since a corrupt file could ask for anything
+    let mut known_polys = HashMap::new();
+    for n in 1..=max_n {
+        let count = u64::from_le_bytes(read_array(input)?) as usize;
+        let mut polys = HashSet::new();
+        for _ in 0..count {
+            let width = u16::from_le_bytes(read_array(input)?) as usize;
+            let height = u16::from_le_bytes(read_array(input)?) as usize;
+            let mut bytes = vec![0; (width * height).div_ceil(8)];
+            input.read_exact(&mut bytes)?;
+            // re-canonicalized on the way in, in case the file was written by a different version
+            let poly = ShapeWithGrid::from_packed(width, height, &bytes).map_err(|e| invalid(&e.to_string()))?;
+            polys.insert(poly);
+        }
+        known_polys.insert(n, polys);
+    }
+    Ok(known_polys)
+}
+
+fn read_array<const N: usize>(input: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    input.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::ErrorKind;
+
+    use crate::cli::Format;
+    use crate::poly_2d::binary::{read_bin, write_bin, MAGIC};
+    use crate::poly_2d::poly::{generate_shapes_up_to_size_sequential, write_polys};
+    use crate::poly_2d::shape::grid::DisplayStyle;
+    use crate::poly_2d::shape::shape_with_grid::ShapeWithGrid;
+
+    #[test]
+    fn should_round_trip_polys() {
+        let polys = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(6);
+        let mut out = Vec::new();
+        write_bin(&mut out, 6, &polys).unwrap();
+        assert!(out.starts_with(MAGIC));
+
+        let read = read_bin(&mut out.as_slice()).unwrap();
+        assert_eq!(read, polys);
+        assert_eq!(read[&6].len(), 60);
+    }
+
+    #[test]
+    fn should_be_smaller_than_compact_text() {
+        let polys = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(8);
+        let mut bin = Vec::new();
+        write_bin(&mut bin, 8, &polys).unwrap();
+        let mut compact = Vec::new();
+        write_polys(&mut compact, 8, Format::Compact, DisplayStyle::default(), None, &polys).unwrap();
+        assert!(bin.len() < compact.len(), "{} vs {}", bin.len(), compact.len());
+    }
+
+    #[test]
+    fn should_reject_truncated_or_foreign_input() {
+        let polys = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(4);
+        let mut out = Vec::new();
+        write_bin(&mut out, 4, &polys).unwrap();
+
+        let truncated = read_bin(&mut &out[..out.len() - 1]).unwrap_err();
+        assert_eq!(truncated.kind(), ErrorKind::UnexpectedEof);
+        let foreign = read_bin(&mut "Polys with size n=1\n".as_bytes()).unwrap_err();
+        assert_eq!(foreign.kind(), ErrorKind::InvalidData);
+
+        // a huge max_n, with nothing after it, fails to read rather than to allocate
+        let mut huge = out[..MAGIC.len() + 3].to_vec();
+        huge.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(read_bin(&mut huge.as_slice()).unwrap_err().kind(), ErrorKind::UnexpectedEof);
+
+        // an unknown version
+        out[MAGIC.len()] = 99;
+        assert_eq!(read_bin(&mut out.as_slice()).unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use crate::cli::Classify;
+use crate::poly_2d::binary::{is_bin_file, read_bin_from_file};
+use crate::poly_2d::poly_error::PolyError;
+use crate::poly_2d::shape::shape_error::ParseError;
+use crate::poly_2d::shape::shape_with_grid::ShapeWithGrid;
+use crate::poly_2d::symmetry::symmetry_class;
+
+pub fn classify_polys(cli: Classify) -> Result<(), PolyError> {
+    for (i, shape) in read_shapes_from_file(&cli.input)?.iter().enumerate() {
+        match shape {
+            Ok(shape) => println!("{}: {} {}", i + 1, shape.to_compact(), symmetry_class(shape)),
+            Err(e) => println!("{}: invalid, {}", i + 1, e),
+        }
+    }
+    Ok(())
+}
+
+// reads either a binary dump, written with --dump-bin, smallest size first, or ascii art
+fn read_shapes_from_file(path: &Path) -> Result<Vec<Result<ShapeWithGrid, ParseError>>, PolyError> {
+    if is_bin_file(path).map_err(|e| PolyError::Read(path.to_path_buf(), e))? {
+        let known_polys = read_bin_from_file(path).map_err(|e| PolyError::Read(path.to_path_buf(), e))?;
+        let mut sizes: Vec<(usize, HashSet<ShapeWithGrid>)> = known_polys.into_iter().collect();
+        sizes.sort_by_key(|(n, _)| *n);
+        return Ok(sizes
+            .into_iter()
+            .flat_map(|(_, polys)| {
+                let mut polys: Vec<ShapeWithGrid> = polys.into_iter().collect();
+                polys.sort();
+                polys
+            })
+            .map(Ok)
+            .collect());
+    }
+    let input = fs::read_to_string(path).map_err(|e| PolyError::Read(path.to_path_buf(), e))?;
+    Ok(read_shapes(&input))
+}
+
+// ascii art shapes, as parsed by from_ascii, separated by blank lines
+fn read_shapes(input: &str) -> Vec<Result<ShapeWithGrid, ParseError>> {
+    let mut shapes = Vec::new();
+    let mut lines: Vec<&str> = Vec::new();
+    for line in input.lines().chain([""]) {
+        if !line.trim().is_empty() {
+            lines.push(line);
+        } else if !lines.is_empty() {
+            shapes.push(ShapeWithGrid::from_ascii(&lines.join("\n")));
+            lines.clear();
+        }
+    }
+    shapes
+}
+
+#[cfg(test)]
+mod test {
+    use crate::poly_2d::binary::write_bin_to_file;
+    use crate::poly_2d::classify::{read_shapes, read_shapes_from_file};
+    use crate::poly_2d::poly::generate_shapes_up_to_size_sequential;
+    use crate::poly_2d::shape::shape_error::ParseError;
+    use crate::poly_2d::shape::shape_with_grid::ShapeWithGrid;
+    use crate::poly_2d::symmetry::{symmetry_class, SymmetryClass};
+
+    #[test]
+    fn should_classify_rotations_the_same() {
+        let path = std::env::temp_dir().join("polycubes_should_classify_rotations_the_same.txt");
+        std::fs::write(&path, "O\nO\nOO\n\nOOO\nO\n").unwrap();
+
+        let shapes = read_shapes_from_file(&path).unwrap();
+        assert_eq!(shapes.len(), 2);
+        let (a, b) = (shapes[0].as_ref().unwrap(), shapes[1].as_ref().unwrap());
+        assert_eq!(a.to_compact(), b.to_compact());
+        assert_eq!(symmetry_class(a), SymmetryClass::Asymmetric);
+        assert_eq!(symmetry_class(b), SymmetryClass::Asymmetric);
+    }
+
+    #[test]
+    fn should_classify_binary_dump_by_size() {
+        let polys = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(4);
+        let path = std::env::temp_dir().join("polycubes_should_classify_binary_dump_by_size.bin");
+        write_bin_to_file(&path, 4, &polys).unwrap();
+
+        let shapes = read_shapes_from_file(&path).unwrap();
+        assert_eq!(shapes.len(), 1 + 1 + 2 + 7);
+        assert_eq!(symmetry_class(shapes[0].as_ref().unwrap()), SymmetryClass::D4);
+        assert_eq!(shapes[10].as_ref().unwrap().points.len(), 4);
+    }
+
+    #[test]
+    fn should_read_invalid_shapes_in_place() {
+        let shapes = read_shapes("\n\nOO\n\nO O\n  \n\nOO\nOO\n\n");
+        assert_eq!(shapes.len(), 3);
+        assert_eq!(symmetry_class(shapes[0].as_ref().unwrap()), SymmetryClass::D2);
+        assert_eq!(shapes[1], Err(ParseError::Disconnected));
+        assert_eq!(symmetry_class(shapes[2].as_ref().unwrap()), SymmetryClass::D4);
+    }
+}
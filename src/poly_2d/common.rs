@@ -15,7 +15,7 @@ pub struct Shape {
 }
 
 impl Shape {
-    pub fn crop(&self) -> ArrayView2<u8> {
+    pub fn crop(&self) -> ArrayView2<'_, u8> {
         let b = self.bounds.clone();
         self.grid.slice(s![b.min_x..=b.max_x, b.min_y..=b.max_y])
     }
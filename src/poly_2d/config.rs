@@ -0,0 +1,214 @@
+use std::path::PathBuf;
+
+use crate::cli::{Algorithm, Connectivity, Format, LatticeType, Poly2d, Shard, TorusSize};
+use crate::poly_2d::poly::PARALLEL_THRESHOLD;
+use crate::poly_2d::shape::grid::DisplayStyle;
+use crate::poly_2d::symmetry::SymmetryClass;
+
+// everything generate_polys needs to know, so it can be called without going through the cli. see Poly2d for what
+// each field does
+#[derive(Debug, Clone)]
+pub struct GenerationConfig {
+    pub max_n: usize,
+    pub algorithm: Algorithm,
+    pub lattice: LatticeType,
+    pub torus: Option<TorusSize>,
+    pub shard: Option<Shard>,
+    pub connectivity: Connectivity,
+    pub count_only: bool,
+    pub max_width: Option<usize>,
+    pub max_height: Option<usize>,
+    pub max_memory_mb: Option<usize>,
+    pub sample: Option<usize>,
+    pub profile: bool,
+    pub children_histogram: bool,
+    pub sort_parents: bool,
+    pub append_origin_only: bool,
+    pub parallel_threshold: usize,
+    pub format: Format,
+    pub style: DisplayStyle,
+    pub report_polys: bool,
+    pub limit: Option<usize>,
+    pub symmetry_classes: bool,
+    pub perimeter_histogram: bool,
+    pub bbox_histogram: bool,
+    pub metrics: bool,
+    pub only_symmetry: Option<SymmetryClass>,
+    pub with_holes: bool,
+    pub dump_canonical: bool,
+    pub self_check: bool,
+    pub compare_algorithms: bool,
+    pub cumulative: bool,
+    pub growth_ratio: bool,
+    pub status_line: bool,
+    pub output: Option<PathBuf>,
+    pub output_dir: Option<PathBuf>,
+    pub manifest: bool,
+    pub dump_bin: Option<PathBuf>,
+    pub csv: Option<PathBuf>,
+    #[cfg(feature = "image")]
+    pub png_dir: Option<PathBuf>,
+    #[cfg(feature = "image")]
+    pub gallery: Option<PathBuf>,
+}
+
+// polyominoes with A32, without any reports or files
+impl Default for GenerationConfig {
+    fn default() -> Self {
+        GenerationConfig {
+            max_n: 0,
+            algorithm: Algorithm::A32,
+            lattice: LatticeType::Square,
+            torus: None,
+            shard: None,
+            connectivity: Connectivity::Edge,
+            count_only: false,
+            max_width: None,
+            max_height: None,
+            max_memory_mb: None,
+            sample: None,
+            profile: false,
+            children_histogram: false,
+            sort_parents: false,
+            append_origin_only: false,
+            parallel_threshold: PARALLEL_THRESHOLD,
+            format: Format::Ascii,
+            style: DisplayStyle::default(),
+            report_polys: false,
+            limit: None,
+            symmetry_classes: false,
+            perimeter_histogram: false,
+            bbox_histogram: false,
+            metrics: false,
+            only_symmetry: None,
+            with_holes: false,
+            dump_canonical: false,
+            self_check: false,
+            compare_algorithms: false,
+            cumulative: false,
+            growth_ratio: false,
+            status_line: false,
+            output: None,
+            output_dir: None,
+            manifest: false,
+            dump_bin: None,
+            csv: None,
+            #[cfg(feature = "image")]
+            png_dir: None,
+            #[cfg(feature = "image")]
+            gallery: None,
+        }
+    }
+}
+
+impl GenerationConfig {
+    // whether generation goes through A32 keeping all polys, which is the only kind of run that writes or reports
+    // them, or grows them with any of the options below
+    pub fn keeps_a32_polys(&self) -> bool {
+        let a32 = matches!(self.algorithm, Algorithm::A32);
+        matches!((self.lattice, self.connectivity), (LatticeType::Square, Connectivity::Edge))
+            && a32
+            && self.torus.is_none()
+            && !self.count_only
+    }
+
+    // the flags that are set, of those only keeps_a32_polys acts on
+    pub fn a32_only_flags(&self) -> Vec<&'static str> {
+        let flags = [
+            ("--max-width", self.max_width.is_some()),
+            ("--max-height", self.max_height.is_some()),
+            ("--max-memory-mb", self.max_memory_mb.is_some()),
+            ("--sample", self.sample.is_some()),
+            ("--profile", self.profile),
+            ("--children-histogram", self.children_histogram),
+            ("--sort-parents", self.sort_parents),
+            ("--append-origin-only", self.append_origin_only),
+            ("--parallel-threshold", self.parallel_threshold != PARALLEL_THRESHOLD),
+            ("--shard", self.shard.is_some()),
+            ("--report-polys", self.report_polys),
+            ("--symmetry-classes", self.symmetry_classes),
+            ("--perimeter-histogram", self.perimeter_histogram),
+            ("--bbox-histogram", self.bbox_histogram),
+            ("--metrics", self.metrics),
+            ("--only-symmetry", self.only_symmetry.is_some()),
+            ("--with-holes", self.with_holes),
+            ("--dump-canonical", self.dump_canonical),
+            ("--self-check", self.self_check),
+            ("--output", self.output.is_some()),
+            ("--output-dir", self.output_dir.is_some()),
+            ("--manifest", self.manifest),
+            ("--dump-bin", self.dump_bin.is_some()),
+            #[cfg(feature = "image")]
+            ("--png-dir", self.png_dir.is_some()),
+            #[cfg(feature = "image")]
+            ("--gallery", self.gallery.is_some()),
+        ];
+        flags.into_iter().filter(|(_, set)| *set).map(|(flag, _)| flag).collect()
+    }
+
+    // the flags that are set, of those that only apply in 2 dimensions. polycubes in 3d are only counted, although
+    // their growth is parallelized the same way
+    pub fn only_2d_flags(&self) -> Vec<&'static str> {
+        let flags = [
+            ("--lattice", !matches!(self.lattice, LatticeType::Square)),
+            ("--connectivity", !matches!(self.connectivity, Connectivity::Edge)),
+            ("--torus", self.torus.is_some()),
+            ("--algorithm", !matches!(self.algorithm, Algorithm::A32)),
+            ("--compare-algorithms", self.compare_algorithms),
+        ];
+        let a32_only_flags = self.a32_only_flags().into_iter().filter(|flag| *flag != "--parallel-threshold");
+        a32_only_flags.chain(flags.into_iter().filter(|(_, set)| *set).map(|(flag, _)| flag)).collect()
+    }
+}
+
+impl From<Poly2d> for GenerationConfig {
+    fn from(cli: Poly2d) -> Self {
+        let default = GenerationConfig::default();
+        GenerationConfig {
+            max_n: cli.max_n,
+            algorithm: match cli.no_canonicalize {
+                true => Algorithm::Fixed,
+                false => cli.algorithm.unwrap_or(default.algorithm),
+            },
+            lattice: cli.lattice.unwrap_or(default.lattice),
+            torus: cli.torus,
+            shard: cli.shard,
+            connectivity: cli.connectivity.unwrap_or(default.connectivity),
+            count_only: cli.count_only,
+            max_width: cli.max_width,
+            max_height: cli.max_height,
+            max_memory_mb: cli.max_memory_mb,
+            sample: cli.sample,
+            profile: cli.profile,
+            children_histogram: cli.children_histogram,
+            sort_parents: cli.sort_parents,
+            append_origin_only: cli.append_origin_only,
+            parallel_threshold: cli.parallel_threshold.unwrap_or(default.parallel_threshold),
+            format: cli.format.unwrap_or(default.format),
+            style: DisplayStyle { filled: cli.fill_char, empty: cli.empty_char, y_up: cli.y_up },
+            report_polys: cli.report_polys,
+            limit: cli.limit,
+            symmetry_classes: cli.symmetry_classes,
+            perimeter_histogram: cli.perimeter_histogram,
+            bbox_histogram: cli.bbox_histogram,
+            metrics: cli.metrics,
+            only_symmetry: cli.only_symmetry,
+            with_holes: cli.with_holes,
+            dump_canonical: cli.dump_canonical,
+            self_check: cli.self_check,
+            compare_algorithms: cli.compare_algorithms,
+            cumulative: cli.cumulative,
+            growth_ratio: cli.growth_ratio,
+            status_line: cli.status_line,
+            output: cli.output,
+            output_dir: cli.output_dir,
+            manifest: cli.manifest,
+            dump_bin: cli.dump_bin,
+            csv: cli.csv,
+            #[cfg(feature = "image")]
+            png_dir: cli.png_dir,
+            #[cfg(feature = "image")]
+            gallery: cli.gallery,
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+use crate::cli::Diff;
+use crate::poly_2d::binary::{is_bin_file, read_bin_from_file};
+use crate::poly_2d::poly_error::PolyError;
+use crate::poly_2d::shape::shape_error::ParseError;
+use crate::poly_2d::shape::shape_with_grid::ShapeWithGrid;
+
+pub fn diff_polys(cli: Diff) -> Result<(), PolyError> {
+    let a = read_polys_from_file(&cli.a, cli.n)?;
+    let b = read_polys_from_file(&cli.b, cli.n)?;
+    let (only_a, only_b) = diff(&a, &b);
+
+    for (path, only) in [(&cli.a, only_a), (&cli.b, only_b)] {
+        println!("only in {}: {}", path.display(), only.len());
+        for poly in only {
+            println!("{}", poly.to_compact());
+        }
+    }
+    Ok(())
+}
+
+// the polys in a but not in b, and the other way around, each sorted
+pub fn diff<'a>(
+    a: &'a HashSet<ShapeWithGrid>,
+    b: &'a HashSet<ShapeWithGrid>,
+) -> (Vec<&'a ShapeWithGrid>, Vec<&'a ShapeWithGrid>) {
+    let mut only_a: Vec<&ShapeWithGrid> = a.difference(b).collect();
+    let mut only_b: Vec<&ShapeWithGrid> = b.difference(a).collect();
+    only_a.sort();
+    only_b.sort();
+    (only_a, only_b)
+}
+
+// reads either a binary dump, written with --dump-bin, or compact text
+fn read_polys_from_file(path: &Path, n: usize) -> Result<HashSet<ShapeWithGrid>, PolyError> {
+    if is_bin_file(path).map_err(|e| PolyError::Read(path.to_path_buf(), e))? {
+        let mut known_polys = read_bin_from_file(path).map_err(|e| PolyError::Read(path.to_path_buf(), e))?;
+        return Ok(known_polys.remove(&n).unwrap_or_default());
+    }
+    let file = File::open(path).map_err(|e| PolyError::Read(path.to_path_buf(), e))?;
+    read_polys(BufReader::new(file), n).map_err(|e| match e {
+        ReadError::Io(e) => PolyError::Read(path.to_path_buf(), e),
+        ReadError::Parse(line, e) => PolyError::Parse(path.to_path_buf(), line, e),
+    })
+}
+
+enum ReadError {
+    Io(io::Error),
+    // 1-based line number
+    Parse(usize, ParseError),
+}
+
+// the polys of size n, as written by write_polys with Format::Compact. other sizes are skipped without parsing
+fn read_polys(input: impl BufRead, n: usize) -> Result<HashSet<ShapeWithGrid>, ReadError> {
+    let header = format!("Polys with size n={}", n);
+    let mut polys = HashSet::new();
+    let mut in_size = false;
+    for (i, line) in input.lines().enumerate() {
+        let line = line.map_err(ReadError::Io)?;
+        if line.starts_with("Polys with size n=") {
+            in_size = line == header;
+        } else if in_size && !line.trim().is_empty() {
+            // re-canonicalized on the way in, in case the file was written by a different version
+            polys.insert(ShapeWithGrid::from_compact(&line).map_err(|e| ReadError::Parse(i + 1, e))?);
+        }
+    }
+    Ok(polys)
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::cli::Format;
+    use crate::poly_2d::binary::write_bin_to_file;
+    use crate::poly_2d::diff::{diff, read_polys, read_polys_from_file, ReadError};
+    use crate::poly_2d::poly::{generate_shapes_up_to_size_sequential, write_polys};
+    use crate::poly_2d::shape::grid::DisplayStyle;
+    use crate::poly_2d::shape::shape_error::ParseError;
+    use crate::poly_2d::shape::shape_with_grid::ShapeWithGrid;
+
+    fn written(max_n: usize) -> Vec<u8> {
+        let polys = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(max_n);
+        let mut out = Vec::new();
+        write_polys(&mut out, max_n, Format::Compact, DisplayStyle::default(), None, &polys).unwrap();
+        out
+    }
+
+    #[test]
+    fn should_read_written_polys_of_one_size() {
+        let polys = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(5);
+        for n in 1..=5 {
+            assert_eq!(read_polys(written(5).as_slice(), n).ok().unwrap(), polys[&n], "n={}", n);
+        }
+        assert!(read_polys(written(5).as_slice(), 6).ok().unwrap().is_empty());
+    }
+
+    #[test]
+    fn should_report_line_of_malformed_poly() {
+        let input = "Polys with size n=1\n1x1:80\nPolys with size n=2\n1x2:c0\nnonsense\n";
+        assert!(read_polys(input.as_bytes(), 1).is_ok());
+        assert!(matches!(read_polys(input.as_bytes(), 2), Err(ReadError::Parse(5, ParseError::Malformed))));
+    }
+
+    #[test]
+    fn should_diff_different_sets() {
+        let tetrominoes = read_polys(written(4).as_slice(), 4).ok().unwrap();
+        // drop the square, and add a pentomino that has no business being there
+        let changed = "Polys with size n=4\n1x4:f0\n2x3:ac\n2x3:b8\n2x3:b4\n2x3:78\n3x2:9c\n2x3:f8\n";
+        let changed: HashSet<ShapeWithGrid> = read_polys(changed.as_bytes(), 4).ok().unwrap();
+
+        let (only_tetrominoes, only_changed) = diff(&tetrominoes, &changed);
+        assert_eq!(only_tetrominoes.iter().map(|poly| poly.to_compact()).collect::<Vec<_>>(), vec!["2x2:f0"]);
+        assert_eq!(only_changed.iter().map(|poly| poly.to_compact()).collect::<Vec<_>>(), vec![ShapeWithGrid::from_compact("2x3:f8").unwrap().to_compact()]);
+    }
+
+    #[test]
+    fn should_read_binary_dump_of_one_size() {
+        let polys = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(5);
+        let path = std::env::temp_dir().join("polycubes_should_read_binary_dump_of_one_size.bin");
+        write_bin_to_file(&path, 5, &polys).unwrap();
+        assert_eq!(read_polys_from_file(&path, 5).unwrap(), polys[&5]);
+        assert!(read_polys_from_file(&path, 6).unwrap().is_empty());
+    }
+
+    #[test]
+    fn should_not_diff_identical_sets() {
+        let tetrominoes = read_polys(written(4).as_slice(), 4).ok().unwrap();
+        let (only_a, only_b) = diff(&tetrominoes, &tetrominoes);
+        assert!(only_a.is_empty() && only_b.is_empty());
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::HashSet;
+
+use dashmap::DashSet;
+use nalgebra::Vector2;
+use rayon::prelude::*;
+
+use crate::poly_2d::poly_error::PolyError;
+use crate::poly_2d::shape::shape_generic::ShapeN;
+use crate::poly_2d::shape::shape_with_grid::ShapeWithGrid;
+use crate::poly_2d::symmetry::to_free;
+
+// the region is a single mask, with bit y * width + x for cell (x, y)
+const MAX_BOX_CELLS: usize = u64::BITS as usize;
+
+pub fn enumerate_polys_in_box(width: usize, height: usize, n: usize) -> Result<(), PolyError> {
+    if width * height > MAX_BOX_CELLS {
+        return Err(PolyError::BoxTooLarge(width, height));
+    }
+    let placements = connected_subsets(width, height, n);
+    let polys = canonical_polys(width, &placements);
+    println!("placements in {}x{}: {}", width, height, placements.len());
+    println!("one-sided: {}", polys.len());
+    println!("free: {}", to_free(&polys).len());
+
+    // sorted, so that output is the same on every run
+    let mut sorted: Vec<&ShapeWithGrid> = polys.iter().collect();
+    sorted.sort();
+    for poly in sorted {
+        println!("\n{}", poly);
+    }
+    Ok(())
+}
+
+// every connected set of n cells within a box of the given size, as masks. unlike generated polys, the same shape
+// in different places or rotations counts once per placement
+pub fn connected_subsets(width: usize, height: usize, n: usize) -> HashSet<u64> {
+    assert!(width * height <= MAX_BOX_CELLS, "a {}x{} box has more than {} cells", width, height, MAX_BOX_CELLS);
+    if n == 0 || n > width * height {
+        return HashSet::new();
+    }
+
+    let mut subsets: HashSet<u64> = (0..width * height).map(|cell| 1 << cell).collect();
+    for _ in 1..n {
+        let grown: DashSet<u64> = DashSet::new();
+        subsets.par_iter().for_each(|&subset| {
+            for cell in cells(subset) {
+                for neighbor in neighbors(width, height, cell) {
+                    if subset & (1 << neighbor) == 0 {
+                        grown.insert(subset | (1 << neighbor));
+                    }
+                }
+            }
+        });
+        subsets = grown.into_iter().collect();
+    }
+    subsets
+}
+
+// the distinct polys among the placements, canonical under rotations as usual
+pub fn canonical_polys(width: usize, placements: &HashSet<u64>) -> HashSet<ShapeWithGrid> {
+    let point = |cell: usize| Vector2::new((cell % width) as i32, (cell / width) as i32);
+    placements.iter().map(|&mask| ShapeWithGrid::new(cells(mask).map(point).collect())).collect()
+}
+
+// indices of the set bits
+fn cells(mask: u64) -> impl Iterator<Item = usize> {
+    (0..u64::BITS as usize).filter(move |i| mask & (1 << i) != 0)
+}
+
+// the cells sharing an edge with the given one, without leaving the box
+fn neighbors(width: usize, height: usize, cell: usize) -> impl Iterator<Item = usize> {
+    let (x, y) = (cell % width, cell / width);
+    [
+        (x + 1 < width).then(|| cell + 1),
+        (x > 0).then(|| cell - 1),
+        (y + 1 < height).then(|| cell + width),
+        (y > 0).then(|| cell - width),
+    ]
+    .into_iter()
+    .flatten()
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::Vector2;
+
+    use crate::poly_2d::in_box::{canonical_polys, connected_subsets};
+    use crate::poly_2d::poly::generate_shapes_up_to_size_sequential;
+    use crate::poly_2d::shape::shape_generic::ShapeN;
+    use crate::poly_2d::shape::shape_with_grid::ShapeWithGrid;
+    use crate::poly_2d::symmetry::to_free;
+
+    #[test]
+    fn should_find_only_l_tromino_in_2x2_box() {
+        // each placement leaves out one of the 4 cells
+        let placements = connected_subsets(2, 2, 3);
+        assert_eq!(placements.len(), 4);
+
+        let polys = canonical_polys(2, &placements);
+        let free = to_free(&polys);
+        assert_eq!(free.len(), 1);
+        let l = ShapeWithGrid::new(vec![Vector2::new(0, 0), Vector2::new(1, 0), Vector2::new(0, 1)]);
+        assert_eq!(polys.into_iter().collect::<Vec<_>>(), vec![l]);
+    }
+
+    #[test]
+    fn should_find_polys_that_fit_in_box() {
+        // all tetrominoes but the straight one fit into 3x3
+        let polys = canonical_polys(3, &connected_subsets(3, 3, 4));
+        let tetrominoes = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(4).remove(&4).unwrap();
+        let expected: Vec<&ShapeWithGrid> = tetrominoes.iter().filter(|poly| poly.fits_in(3, 3)).collect();
+        assert_eq!(polys.len(), 6);
+        assert_eq!(expected.len(), 6);
+        assert!(expected.iter().all(|poly| polys.contains(*poly)));
+        assert_eq!(to_free(&polys).len(), 4);
+    }
+
+    #[test]
+    fn should_find_nothing_bigger_than_box() {
+        assert!(connected_subsets(2, 2, 5).is_empty());
+        assert!(connected_subsets(2, 2, 0).is_empty());
+        assert_eq!(connected_subsets(2, 2, 4).len(), 1);
+    }
+}
@@ -0,0 +1,63 @@
+use nalgebra::{Rotation2, Vector2};
+
+use crate::poly_2d::moves::{MOVES32, MOVES_HEX, MOVES_KING};
+use crate::poly_2d::rotation::{ROTATIONS32, ROTATIONS_HEX};
+
+// the cells polys are made of, given by how cells neighbor each other and how the lattice can be rotated onto itself
+pub trait Lattice {
+    fn moves() -> &'static [Vector2<i32>];
+
+    fn rotations() -> &'static [Rotation2<i32>];
+}
+
+// polyominoes
+pub struct Square;
+
+impl Lattice for Square {
+    fn moves() -> &'static [Vector2<i32>] {
+        MOVES32
+    }
+
+    fn rotations() -> &'static [Rotation2<i32>] {
+        ROTATIONS32
+    }
+}
+
+// polyominoes that count as different when rotated, i.e. fixed polyominoes. only the identity is a rotation
+pub struct SquareFixed;
+
+impl Lattice for SquareFixed {
+    fn moves() -> &'static [Vector2<i32>] {
+        MOVES32
+    }
+
+    fn rotations() -> &'static [Rotation2<i32>] {
+        &ROTATIONS32[..1]
+    }
+}
+
+// polyplets, which are squares that neighbor each other also diagonally, like a king moves in chess
+pub struct King;
+
+impl Lattice for King {
+    fn moves() -> &'static [Vector2<i32>] {
+        MOVES_KING
+    }
+
+    fn rotations() -> &'static [Rotation2<i32>] {
+        ROTATIONS32
+    }
+}
+
+// polyhexes
+pub struct Hex;
+
+impl Lattice for Hex {
+    fn moves() -> &'static [Vector2<i32>] {
+        MOVES_HEX
+    }
+
+    fn rotations() -> &'static [Rotation2<i32>] {
+        ROTATIONS_HEX
+    }
+}
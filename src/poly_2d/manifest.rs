@@ -0,0 +1,94 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::poly_2d::shape::shape_with_grid::ShapeWithGrid;
+
+pub const MANIFEST: &str = "manifest.json";
+
+// FNV-1a, 64 bit. the std hashers may change between releases, which would break comparing manifests over time
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+// writes manifest.json next to the size-<n>.txt files written by --output-dir, with one entry per file: its name, the
+// size and number of its polys, and a hash of the polys. the hash doesn't depend on the format the file was written
+// in, so tools can check that two dumps hold the same polys without comparing files. one entry per line, so it's also
+// easy to read without a json parser
+pub fn write_manifest(dir: &Path, max_n: usize, known_polys: &HashMap<usize, HashSet<ShapeWithGrid>>) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(dir.join(MANIFEST))?);
+    writeln!(writer, "{{")?;
+    writeln!(writer, "  \"hash\": \"fnv1a-64 of the sorted compact encodings, each followed by a newline\",")?;
+    writeln!(writer, "  \"files\": [")?;
+    for n in 1..=max_n {
+        let polys = &known_polys[&n];
+        let separator = if n < max_n { "," } else { "" };
+        writeln!(
+            writer,
+            "    {{\"file\": \"size-{}.txt\", \"size\": {}, \"count\": {}, \"hash\": \"{:016x}\"}}{}",
+            n,
+            n,
+            polys.len(),
+            content_hash(polys),
+            separator
+        )?;
+    }
+    writeln!(writer, "  ]")?;
+    writeln!(writer, "}}")?;
+    // dropping a BufWriter swallows errors, so flush explicitly
+    writer.flush()
+}
+
+// the hash of the compact encodings of the polys, concatenated in sorted order so that it's the same on every run
+pub fn content_hash(polys: &HashSet<ShapeWithGrid>) -> u64 {
+    let mut sorted: Vec<&ShapeWithGrid> = polys.iter().collect();
+    sorted.sort();
+    sorted.iter().fold(FNV_OFFSET, |hash, poly| fnv1a(fnv1a(hash, poly.to_compact().as_bytes()), b"\n"))
+}
+
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cli::Format;
+    use crate::poly_2d::config::GenerationConfig;
+    use crate::poly_2d::manifest::{fnv1a, FNV_OFFSET, MANIFEST};
+    use crate::poly_2d::poly::generate_polys;
+
+    #[test]
+    fn should_match_fnv1a_reference() {
+        assert_eq!(fnv1a(FNV_OFFSET, b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(FNV_OFFSET, b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a(FNV_OFFSET, b"foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn should_record_counts_of_files_and_same_hashes_on_rerun() {
+        let dir = std::env::temp_dir().join("polycubes_should_record_counts_of_files_and_same_hashes_on_rerun");
+        let _ = std::fs::remove_dir_all(&dir);
+        let config = GenerationConfig {
+            max_n: 6,
+            format: Format::Compact,
+            output_dir: Some(dir.clone()),
+            manifest: true,
+            ..Default::default()
+        };
+        generate_polys(config.clone()).unwrap();
+        let manifest = std::fs::read_to_string(dir.join(MANIFEST)).unwrap();
+
+        let entries: Vec<&str> = manifest.lines().filter(|line| line.contains("\"file\"")).collect();
+        assert_eq!(entries.len(), 6);
+        for (n, entry) in (1..=6).zip(entries) {
+            let file = format!("size-{}.txt", n);
+            assert!(entry.contains(&format!("\"file\": \"{}\"", file)), "{}", entry);
+            let lines = std::fs::read_to_string(dir.join(file)).unwrap().lines().count();
+            assert!(entry.contains(&format!("\"count\": {},", lines - 1)), "{}", entry);
+        }
+
+        // the hashes don't depend on the format, or on the order polys were generated in
+        generate_polys(GenerationConfig { format: Format::Ascii, ..config }).unwrap();
+        assert_eq!(std::fs::read_to_string(dir.join(MANIFEST)).unwrap(), manifest);
+    }
+}
@@ -1,6 +1,20 @@
 mod common;
-mod shape;
+pub mod config;
+pub mod shape;
 pub mod poly;
+pub mod poly_error;
 pub mod snake;
-mod rotation;
-mod moves;
\ No newline at end of file
+pub mod rotation;
+mod moves;
+pub mod symmetry;
+pub mod torus;
+pub mod in_box;
+pub mod repl;
+mod lattice;
+mod analysis;
+pub mod classify;
+pub mod diff;
+pub mod binary;
+pub mod manifest;
+#[cfg(feature = "image")]
+pub mod render;
@@ -1,4 +1,4 @@
-use nalgebra::Vector2;
+use nalgebra::{Vector2, Vector3};
 
 pub static MOVES32: &[Vector2<i32>] = &[
     Vector2::new(0, 1),
@@ -12,4 +12,34 @@ pub static MOVES8: &[Vector2<i8>] = &[
     Vector2::new(0, -1),
     Vector2::new(1, 0),
     Vector2::new(-1, 0),
-];
\ No newline at end of file
+];
+
+pub static MOVES_KING: &[Vector2<i32>] = &[
+    Vector2::new(0, 1),
+    Vector2::new(0, -1),
+    Vector2::new(1, 0),
+    Vector2::new(-1, 0),
+    Vector2::new(1, 1),
+    Vector2::new(1, -1),
+    Vector2::new(-1, 1),
+    Vector2::new(-1, -1),
+];
+
+// axial coordinates, where (q, r) are the hexagon's column and diagonal row
+pub static MOVES_HEX: &[Vector2<i32>] = &[
+    Vector2::new(1, 0),
+    Vector2::new(-1, 0),
+    Vector2::new(0, 1),
+    Vector2::new(0, -1),
+    Vector2::new(1, -1),
+    Vector2::new(-1, 1),
+];
+
+pub static MOVES_CUBE: &[Vector3<i32>] = &[
+    Vector3::new(0, 0, 1),
+    Vector3::new(0, 0, -1),
+    Vector3::new(0, 1, 0),
+    Vector3::new(0, -1, 0),
+    Vector3::new(1, 0, 0),
+    Vector3::new(-1, 0, 0),
+];
@@ -1,164 +1,892 @@
 use std::{
-    collections::{HashMap, HashSet},
-    time::Instant,
+    cmp::Ordering as CmpOrdering,
+    collections::{BTreeMap, HashMap, HashSet},
+    fs::{self, File},
+    hash::Hash,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
 };
 
+use dashmap::DashSet;
 use lazy_static::lazy_static;
-use nalgebra::Vector2;
+use nalgebra::{ClosedAddAssign, SVector, Scalar, Vector2};
+use num_traits::Zero;
 use rayon::prelude::*;
 
-use crate::cli::{Algorithm, Poly2d};
-use crate::poly_2d::moves::{MOVES32, MOVES8};
-use crate::poly_2d::rotation::ROTATIONS8;
+use crate::cli::{Algorithm, Connectivity, Format, LatticeType, Shard};
+use crate::poly_2d::analysis::{report_bbox_histogram, report_holes, report_metrics, report_perimeter_histogram};
+use crate::poly_2d::binary::write_bin_to_file;
+use crate::poly_2d::config::GenerationConfig;
+use crate::poly_2d::manifest::{write_manifest, MANIFEST};
+use crate::poly_2d::moves::MOVES32;
+use crate::poly_2d::poly_error::PolyError;
+#[cfg(feature = "image")]
+use crate::poly_2d::render::{write_gallery, write_pngs};
+use crate::poly_2d::shape::connectivity::is_connected;
+use crate::poly_2d::shape::grid::DisplayStyle;
+use crate::poly_2d::shape::shape_cubes::ShapeCubes;
+use crate::poly_2d::shape::shape_generic::ShapeN;
 use crate::poly_2d::shape::shape_minimal::ShapeMinimal;
-use crate::poly_2d::shape::shape_with_grid::ShapeWithGrid;
+use crate::poly_2d::shape::shape_on_lattice::{ShapeFixed, ShapeHex, ShapePolyplet};
+use crate::poly_2d::shape::shape_with_grid::{canonical_grid, grid_points, Fingerprint, ShapeWithGrid, Styled};
+use crate::poly_2d::symmetry::{
+    check_burnside, report_retained_symmetry, report_symmetry_classes, retain_symmetry, rotated_duplicates,
+};
+use crate::poly_2d::torus::generate_torus_shapes_up_to_size_with_stats;
+use crate::status_line::{self, set_status_line, status_line, SizeStatus};
+use crate::verbosity::{verbosity, Verbosity};
+
+#[cfg(feature = "image")]
+const PNG_CELL_PX: u32 = 16;
 
 lazy_static! {
     static ref NUM: format_num::NumberFormat = format_num::NumberFormat::new();
 }
 
-pub fn generate_polys(cli: Poly2d) {
-    let alg = cli.algorithm.clone().unwrap_or(Algorithm::A32);
+#[derive(Debug, Clone)]
+pub struct GenerationStats {
+    pub size: usize,
+    pub elapsed: Duration,
+    // neighbors of all points of the polys of the previous size, occupied or not
+    pub points_tried: usize,
+    // polys grown from the previous size, including duplicates
+    pub polys_tried: usize,
+    // distinct polys
+    pub found: usize,
+    // only with --profile, and only for sizes grown from a previous one
+    pub profile: Option<ExpansionProfile>,
+    // how many polys of the previous size grew into each number of distinct polys of this size. only with
+    // --children-histogram, and only for sizes grown from a previous one
+    pub children: Option<BTreeMap<usize, usize>>,
+}
 
-    println!("generating polycubes (in 2d) up to size {} with algorithm {}", cli.max_n, alg);
+impl GenerationStats {
+    // how many polys were grown for each distinct one kept, i.e. what deduplicating by brute force costs. none if
+    // nothing was found
+    pub fn overcount(&self) -> Option<f64> {
+        (self.found > 0).then(|| self.polys_tried as f64 / self.found as f64)
+    }
+}
 
-    match alg {
-        Algorithm::A32 => {
-            let polys = generate_shape_with_grid_up_to(cli.max_n);
-            if cli.report_polys {
-                report_polys(cli, polys);
-            }
+// how long growing each poly of the previous size took
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpansionProfile {
+    pub parents: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl ExpansionProfile {
+    fn of(mut times: Vec<Duration>) -> Option<ExpansionProfile> {
+        times.sort_unstable();
+        Some(ExpansionProfile {
+            parents: times.len(),
+            min: *times.first()?,
+            median: times[times.len() / 2],
+            max: *times.last()?,
+        })
+    }
+}
+
+// what to do differently while growing a single size
+struct GrowOptions<S> {
+    // stop after this many distinct polys
+    sample: Option<usize>,
+    // time each parent
+    profile: bool,
+    // count the distinct children of each parent
+    children: bool,
+    // grow the parents in this order, rather than in the order of the set they're stored in
+    parent_order: Option<fn(&S, &S) -> CmpOrdering>,
+    // only grow a parent into cells that are leaves of the child, i.e. have a single neighbor in the parent, unless
+    // the child has no leaves at all. a child with a leaf is still grown from the parent without that leaf, and the
+    // other candidates are skipped before they're built and canonicalized. see is_leaf_site
+    leaf_sites_only: bool,
+    // grow the parents in parallel only if there are at least this many of them, since for a handful of parents
+    // handing them to rayon costs more than it saves. usize::MAX grows them one after the other on the current thread,
+    // so that sampling picks the same polys every time
+    parallel_threshold: usize,
+    // only grow this shard of the parents, as sorted by parent_order, which must be set
+    shard: Option<Shard>,
+    // stop as soon as all polys, of this size and those before, take up more than this many bytes as estimated by the
+    // fn. checked on every new poly, so that a single big size can't go far over
+    budget: Option<(usize, EstimateBytes<S>)>,
+}
+
+type EstimateBytes<S> = fn(&S) -> usize;
+
+// the default parallel_threshold
+pub const PARALLEL_THRESHOLD: usize = 1000;
+
+// derive would require S: Default etc., although S is only used behind a fn pointer
+impl<S> Default for GrowOptions<S> {
+    fn default() -> Self {
+        GrowOptions {
+            sample: None,
+            profile: false,
+            children: false,
+            parent_order: None,
+            leaf_sites_only: false,
+            parallel_threshold: PARALLEL_THRESHOLD,
+            shard: None,
+            budget: None,
         }
-        Algorithm::B8 => {
-            generate_shape_minimal_up_to(cli.max_n);
+    }
+}
+
+impl<S> Clone for GrowOptions<S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Copy for GrowOptions<S> {}
+
+// generates, reports and writes polys as configured, and returns the stats of each generated size
+pub fn generate_polys(config: GenerationConfig) -> Result<Vec<GenerationStats>, PolyError> {
+    let alg = config.algorithm.clone();
+    let format = config.format;
+    if verbosity() >= Verbosity::Verbose {
+        println!("{:?}", config);
+    }
+    set_status_line(config.status_line && verbosity() >= Verbosity::Normal);
+    if !config.keeps_a32_polys() {
+        if let Some(flag) = config.a32_only_flags().first() {
+            return Err(PolyError::OnlyA32(flag));
+        }
+    }
+    if config.max_n == 0 {
+        report_progress(format_args!("nothing to generate for size 0\n"));
+        return Ok(Vec::new());
+    }
+
+    let stats = match (config.lattice, config.connectivity) {
+        (LatticeType::Hex, Connectivity::King) => return Err(PolyError::KingOnHex),
+        (LatticeType::Square, Connectivity::Edge) if config.torus.is_some() => {
+            let torus = config.torus.unwrap();
+            report_progress(format_args!(
+                "generating polyominoes on a {}x{} torus up to size {}\n",
+                torus.width, torus.height, config.max_n
+            ));
+            generate_torus_shapes_up_to_size_with_stats(config.max_n, torus).1
+        }
+        (_, _) if config.torus.is_some() => return Err(PolyError::TorusOnlySquare),
+        (LatticeType::Hex, _) => {
+            report_progress(format_args!("generating polyhexes up to size {}\n", config.max_n));
+            generate_shapes_up_to_size_with_stats::<ShapeHex, i32>(config.max_n).1
+        }
+        (_, Connectivity::King) => {
+            report_progress(format_args!("generating polyplets up to size {}\n", config.max_n));
+            generate_shapes_up_to_size_with_stats::<ShapePolyplet, i32>(config.max_n).1
+        }
+        _ => {
+            report_progress(format_args!(
+                "generating polycubes (in 2d) up to size {} with algorithm {}\n",
+                config.max_n, alg
+            ));
+            match alg {
+                Algorithm::A32 if config.count_only => count_shapes_up_to_size(config.max_n),
+                Algorithm::A32 => {
+                    let max_width = config.max_width.unwrap_or(usize::MAX);
+                    let max_height = config.max_height.unwrap_or(usize::MAX);
+                    let keep = |poly: &ShapeWithGrid| poly.fits_in(max_width, max_height);
+                    let max_bytes = config.max_memory_mb.map(|mb| mb.saturating_mul(1024 * 1024));
+                    let options = GrowOptions {
+                        sample: config.sample,
+                        profile: config.profile,
+                        children: config.children_histogram,
+                        // shards are split by sorted order
+                        parent_order: (config.sort_parents || config.shard.is_some())
+                            .then_some(ShapeWithGrid::cmp as fn(&_, &_) -> _),
+                        leaf_sites_only: config.append_origin_only,
+                        parallel_threshold: config.parallel_threshold,
+                        shard: config.shard,
+                        budget: max_bytes.map(|max_bytes| (max_bytes, ShapeWithGrid::estimated_bytes as fn(&_) -> _)),
+                    };
+                    let (mut polys, stats) = generate_shapes_up_to_size_while(config.max_n, &keep, options, &|polys| {
+                        max_bytes.is_none_or(|max_bytes| fits_in_memory(polys, max_bytes))
+                    });
+                    // the reports below start on a line of their own, not at the end of the painted status line
+                    status_line::finish();
+                    // a memory budget may have stopped generation early
+                    let max_n = stats.len();
+                    if max_n < config.max_n {
+                        eprintln!(
+                            "stopped after size {}, size {} would exceed the memory budget of {} MB",
+                            max_n,
+                            max_n + 1,
+                            config.max_memory_mb.unwrap()
+                        );
+                    }
+                    if config.self_check {
+                        // Burnside's lemma only holds for every poly of a size, so not for sizes cut short by a
+                        // filter or sample, or for the sharded size
+                        let filtered = config.max_width.is_some() || config.max_height.is_some();
+                        let complete_n = if filtered || config.sample.is_some() {
+                            0
+                        } else if config.shard.is_some() {
+                            max_n - 1
+                        } else {
+                            max_n
+                        };
+                        self_check(max_n, complete_n, &polys)?;
+                    }
+                    // the reports and files below only see the polys that are left
+                    if let Some(class) = config.only_symmetry {
+                        retain_symmetry(&mut polys, class);
+                        report_retained_symmetry(max_n, &polys, class);
+                    }
+                    if config.report_polys {
+                        report_polys(max_n, format, config.style, config.limit, &polys);
+                    }
+                    if config.symmetry_classes {
+                        report_symmetry_classes(max_n, &polys);
+                    }
+                    if config.perimeter_histogram {
+                        report_perimeter_histogram(max_n, &polys);
+                    }
+                    if config.bbox_histogram {
+                        report_bbox_histogram(max_n, &polys);
+                    }
+                    if config.metrics {
+                        report_metrics(max_n, &polys);
+                    }
+                    if config.with_holes {
+                        report_holes(max_n, &polys);
+                    }
+                    if config.dump_canonical {
+                        report_canonical(max_n, &polys);
+                    }
+                    #[cfg(feature = "image")]
+                    if let (Some(dir), Some(largest)) = (&config.png_dir, polys.get(&max_n)) {
+                        write_pngs(dir, max_n, largest, PNG_CELL_PX, config.style.y_up).map_err(|e| PolyError::Write(dir.clone(), e))?;
+                    }
+                    #[cfg(feature = "image")]
+                    if let (Some(path), Some(largest)) = (&config.gallery, polys.get(&max_n)) {
+                        write_gallery(path, largest, PNG_CELL_PX, config.style.y_up).map_err(|e| PolyError::Write(path.clone(), e))?;
+                    }
+                    if let Some(path) = &config.output {
+                        write_polys_to_file(path, max_n, format, config.style, &polys).map_err(|e| PolyError::Write(path.clone(), e))?;
+                    }
+                    if let Some(dir) = &config.output_dir {
+                        write_polys_to_dir(dir, max_n, format, config.style, &polys)?;
+                        // only once all sizes are written, so a manifest is never for a partial dump
+                        if config.manifest {
+                            write_manifest(dir, max_n, &polys).map_err(|e| PolyError::Write(dir.join(MANIFEST), e))?;
+                        }
+                    }
+                    if let Some(path) = &config.dump_bin {
+                        write_bin_to_file(path, max_n, &polys).map_err(|e| PolyError::Write(path.clone(), e))?;
+                    }
+                    stats
+                }
+                Algorithm::B8 => generate_shapes_up_to_size_with_stats::<ShapeMinimal, i8>(config.max_n).1,
+                Algorithm::Fixed => generate_shapes_up_to_size_with_stats::<ShapeFixed, i32>(config.max_n).1,
+            }
         }
+    };
+
+    report_stats(&config, &stats)?;
+    Ok(stats)
+}
+
+// generates polycubes in 3 dimensions as configured. only counts them, so of all flags only those reporting the
+// counts apply
+pub fn generate_polycubes_3d(config: GenerationConfig) -> Result<Vec<GenerationStats>, PolyError> {
+    if verbosity() >= Verbosity::Verbose {
+        println!("{:?}", config);
     }
+    if let Some(flag) = config.only_2d_flags().first() {
+        return Err(PolyError::Only2d(flag));
+    }
+    set_status_line(config.status_line && verbosity() >= Verbosity::Normal);
+    if config.max_n == 0 {
+        report_progress(format_args!("nothing to generate for size 0\n"));
+        return Ok(Vec::new());
+    }
+    report_progress(format_args!("generating polycubes in 3d up to size {}\n", config.max_n));
+    let options = GrowOptions { parallel_threshold: config.parallel_threshold, ..Default::default() };
+    let (_, stats) = generate_shapes_up_to_size_while::<ShapeCubes, i32, 3>(config.max_n, &|_| true, options, &|_| true);
+    report_stats(&config, &stats)?;
+    Ok(stats)
+}
+
+// the reports and files made from the stats alone, which every kind of run has
+fn report_stats(config: &GenerationConfig, stats: &[GenerationStats]) -> Result<(), PolyError> {
+    status_line::finish();
+    if config.profile {
+        report_profile(stats);
+    }
+
+    if config.children_histogram {
+        report_children(stats);
+    }
+
+    if config.cumulative {
+        report_cumulative(stats);
+    }
+
+    if config.growth_ratio {
+        report_growth_ratios(stats);
+    }
+
+    if let Some(path) = &config.csv {
+        write_csv_to_file(path, stats).map_err(|e| PolyError::Write(path.clone(), e))?;
+    }
+
+    if config.compare_algorithms {
+        compare_algorithms(config.max_n)?;
+    }
+
+    let total: usize = stats.iter().map(|s| s.found).sum();
+    report_progress(format_args!("total up to size {}: {}\n", stats.len(), total));
+    Ok(())
+}
+
+// generates all polys up to max_n with both square lattice implementations, which must agree on every count
+fn compare_algorithms(max_n: usize) -> Result<(), PolyError> {
+    report_progress(format_args!("comparing algorithms A32 and B8 up to size {}\n", max_n));
+    let found = |stats: Vec<GenerationStats>| stats.iter().map(|s| s.found).collect::<Vec<_>>();
+    let a32 = found(generate_shapes_up_to_size_with_stats::<ShapeWithGrid, i32>(max_n).1);
+    let b8 = found(generate_shapes_up_to_size_with_stats::<ShapeMinimal, i8>(max_n).1);
+    let disagreements = disagreements(&a32, &b8);
+    if !disagreements.is_empty() {
+        return Err(PolyError::AlgorithmsDisagree(disagreements));
+    }
+    report_progress(format_args!("algorithms A32 and B8 agree up to size {}\n", max_n));
+    Ok(())
+}
+
+// (n, a, b) for each size whose counts differ, starting from size 1
+fn disagreements(a: &[usize], b: &[usize]) -> Vec<(usize, usize, usize)> {
+    a.iter()
+        .zip(b)
+        .enumerate()
+        .filter(|(_, (a, b))| a != b)
+        .map(|(i, (a, b))| (i + 1, *a, *b))
+        .collect()
+}
+
+// how many polys there are of all sizes together
+pub fn total_count<S>(known_polys: &HashMap<usize, HashSet<S>>) -> usize {
+    known_polys.values().map(|polys| polys.len()).sum()
+}
+
+pub fn generate_shapes_up_to_size<S, T>(max_n: usize) -> HashMap<usize, HashSet<S>>
+where
+    S: ShapeN<T, 2> + Hash + Eq + Send + Sync,
+    T: Scalar + Zero + ClosedAddAssign + Send + Sync,
+{
+    generate_shapes_up_to_size_with_stats(max_n).0
+}
+
+pub fn generate_shapes_up_to_size_with_stats<S, T>(max_n: usize) -> (HashMap<usize, HashSet<S>>, Vec<GenerationStats>)
+where
+    S: ShapeN<T, 2> + Hash + Eq + Send + Sync,
+    T: Scalar + Zero + ClosedAddAssign + Send + Sync,
+{
+    generate_filtered_shapes_up_to_size(max_n, &|_| true)
+}
+
+// only keeps, and grows, polys for which keep returns true. so keep must also reject all polys that can be grown
+// from a rejected one, otherwise they may be missed
+pub fn generate_filtered_shapes_up_to_size<S, T>(
+    max_n: usize,
+    keep: &(impl Fn(&S) -> bool + Sync),
+) -> (HashMap<usize, HashSet<S>>, Vec<GenerationStats>)
+where
+    S: ShapeN<T, 2> + Hash + Eq + Send + Sync,
+    T: Scalar + Zero + ClosedAddAssign + Send + Sync,
+{
+    generate_shapes_up_to_size_while(max_n, keep, GrowOptions::default(), &|_| true)
+}
+
+// like generate_shapes_up_to_size, but each size stops growing once sample distinct polys have been found, and only
+// those are grown further. so each size is a subset of all polys of that size, without duplicates, but which subset
+// depends on how the threads happen to be scheduled
+pub fn generate_sampled_shapes_up_to_size<S, T>(max_n: usize, sample: usize) -> HashMap<usize, HashSet<S>>
+where
+    S: ShapeN<T, 2> + Hash + Eq + Send + Sync,
+    T: Scalar + Zero + ClosedAddAssign + Send + Sync,
+{
+    let options = GrowOptions { sample: Some(sample), ..Default::default() };
+    generate_shapes_up_to_size_while(max_n, &|_| true, options, &|_| true).0
+}
+
+// like generate_shapes_up_to_size, but on the current thread only. slower, but for the same max_n the result is always
+// the same, which is what tests comparing generated polys want
+pub fn generate_shapes_up_to_size_sequential<S, T>(max_n: usize) -> HashMap<usize, HashSet<S>>
+where
+    S: ShapeN<T, 2> + Hash + Eq + Send + Sync,
+    T: Scalar + Zero + ClosedAddAssign + Send + Sync,
+{
+    let options = GrowOptions { parallel_threshold: usize::MAX, ..Default::default() };
+    generate_shapes_up_to_size_while(max_n, &|_| true, options, &|_| true).0
+}
+
+// like generate_sampled_shapes_up_to_size, but on the current thread only, and growing the parents in order. so it
+// always picks the same sample
+pub fn generate_sampled_shapes_up_to_size_sequential<S, T>(max_n: usize, sample: usize) -> HashMap<usize, HashSet<S>>
+where
+    S: ShapeN<T, 2> + Hash + Ord + Send + Sync,
+    T: Scalar + Zero + ClosedAddAssign + Send + Sync,
+{
+    let options = GrowOptions {
+        sample: Some(sample),
+        parent_order: Some(S::cmp),
+        parallel_threshold: usize::MAX,
+        ..Default::default()
+    };
+    generate_shapes_up_to_size_while(max_n, &|_| true, options, &|_| true).0
+}
+
+// like generate_filtered_shapes_up_to_size, but stops as soon as the polys use more than max_bytes, as estimated by
+// ShapeWithGrid::estimated_bytes. the size that went over is dropped, so all returned sizes are complete
+pub fn generate_budgeted_shapes_up_to_size(
+    max_n: usize,
+    max_bytes: usize,
+    keep: &(impl Fn(&ShapeWithGrid) -> bool + Sync),
+) -> (HashMap<usize, HashSet<ShapeWithGrid>>, Vec<GenerationStats>) {
+    let options = GrowOptions { budget: Some((max_bytes, ShapeWithGrid::estimated_bytes)), ..Default::default() };
+    // growth stops early once over budget, which leaves the size incomplete, and it's dropped here
+    generate_shapes_up_to_size_while(max_n, keep, options, &|known_polys| {
+        fits_in_memory(known_polys, max_bytes)
+    })
+}
+
+// whether growing the parent into site gives a child that should be grown from this parent, given the parent's
+// leaves. that's when the site is a leaf of the child, or when the child has no leaf at all. adding the site only
+// adds neighbors, so the child's only other possible leaves are those of the parent that aren't next to the site.
+// a leaf can always be removed without disconnecting the rest, so every child with a leaf is grown from the parent
+// without it, and only needs to be grown there
+fn is_leaf_site<T, const D: usize>(
+    parent: &[SVector<T, D>],
+    leaves: &[SVector<T, D>],
+    moves: &[SVector<T, D>],
+    site: &SVector<T, D>,
+) -> bool
+where
+    T: Scalar + ClosedAddAssign,
+{
+    let neighbors = moves.iter().filter(|m| parent.contains(&(site + *m))).count();
+    neighbors == 1 || leaves.iter().all(|leaf| moves.iter().any(|m| &(leaf + m) == site))
 }
 
-fn generate_shape_with_grid_up_to(max_n: usize) -> HashMap<usize, HashSet<ShapeWithGrid>> {
-    let mut known_polys: HashMap<usize, HashSet<ShapeWithGrid>> = HashMap::new();
+// the points of the shape that have exactly one neighbor in it
+fn leaves<T, const D: usize>(points: &[SVector<T, D>], moves: &[SVector<T, D>]) -> Vec<SVector<T, D>>
+where
+    T: Scalar + ClosedAddAssign,
+{
+    let neighbors = |p: &SVector<T, D>| moves.iter().filter(|m| points.contains(&(p + *m))).count();
+    points.iter().filter(|p| neighbors(p) == 1).cloned().collect()
+}
+
+fn fits_in_memory(known_polys: &HashMap<usize, HashSet<ShapeWithGrid>>, max_bytes: usize) -> bool {
+    known_polys.values().flatten().map(|poly| poly.estimated_bytes()).sum::<usize>() <= max_bytes
+}
+
+// generates one size after the other for as long as proceed returns true for all polys so far
+fn generate_shapes_up_to_size_while<S, T, const D: usize>(
+    max_n: usize,
+    keep: &(impl Fn(&S) -> bool + Sync),
+    options: GrowOptions<S>,
+    proceed: &impl Fn(&HashMap<usize, HashSet<S>>) -> bool,
+) -> (HashMap<usize, HashSet<S>>, Vec<GenerationStats>)
+where
+    S: ShapeN<T, D> + Hash + Eq + Send + Sync,
+    T: Scalar + Zero + ClosedAddAssign + Send + Sync,
+{
+    let mut known_polys: HashMap<usize, HashSet<S>> = HashMap::new();
+    let mut stats = Vec::with_capacity(max_n);
     for n in 1..=max_n {
-        let polys = generate_shape_with_grid_level(n, &known_polys);
+        // smaller sizes are needed whole to grow the shard of the largest
+        let options = GrowOptions { shard: options.shard.filter(|_| n == max_n), ..options };
+        let (polys, size_stats) = grow_shapes_with_size(n, &known_polys, keep, options);
         known_polys.entry(n).or_insert(polys);
+        if !proceed(&known_polys) {
+            known_polys.remove(&n);
+            break;
+        }
+        stats.push(size_stats);
     }
-    known_polys
+    (known_polys, stats)
+}
+
+// grows every poly of size n - 1 in known_polys by one cell, and returns the distinct canonical polys of size n that
+// come out of it. known_polys must hold size n - 1, unless n is 1, but needs no other sizes. panics if it doesn't
+pub fn generate_shapes_with_size<S, T>(n: usize, known_polys: &HashMap<usize, HashSet<S>>) -> (HashSet<S>, GenerationStats)
+where
+    S: ShapeN<T, 2> + Hash + Eq + Send + Sync,
+    T: Scalar + Zero + ClosedAddAssign + Send + Sync,
+{
+    grow_shapes_with_size(n, known_polys, &|_| true, GrowOptions::default())
 }
 
-fn generate_shape_with_grid_level(
+// like generate_shapes_with_size, but only grows the given shard of the polys of size n - 1 in sorted order. merging
+// the polys of all shards with merge_sets gives all polys of size n, so separate processes can grow a shard each
+pub fn generate_shard_with_size(
     n: usize,
     known_polys: &HashMap<usize, HashSet<ShapeWithGrid>>,
-) -> HashSet<ShapeWithGrid> {
+    shard: Shard,
+) -> (HashSet<ShapeWithGrid>, GenerationStats) {
+    let options = GrowOptions { parent_order: Some(ShapeWithGrid::cmp), shard: Some(shard), ..Default::default() };
+    grow_shapes_with_size(n, known_polys, &|_| true, options)
+}
+
+fn grow_shapes_with_size<S, T, const D: usize>(
+    n: usize,
+    known_polys: &HashMap<usize, HashSet<S>>,
+    keep: &(impl Fn(&S) -> bool + Sync),
+    options: GrowOptions<S>,
+) -> (HashSet<S>, GenerationStats)
+where
+    S: ShapeN<T, D> + Hash + Eq + Send + Sync,
+    T: Scalar + Zero + ClosedAddAssign + Send + Sync,
+{
+    assert!(
+        n == 1 || known_polys.contains_key(&(n - 1)),
+        "polys of size {} are needed to grow size {}",
+        n - 1,
+        n
+    );
     let start = Instant::now();
-    print!("size: {: >2}... ", n);
+    report_size_start(n);
+    let sample = options.sample;
+    let status = status_line().then(|| SizeStatus::new(n, start));
 
     if n == 1 {
-        report_performance(start, 1, 1, 1);
-        return HashSet::from([ShapeWithGrid::canonical(vec![Vector2::new(0, 0)])]);
-    }
-
-    let prev_polys: &HashSet<ShapeWithGrid> = &known_polys[&(n - 1)];
-    let result: (usize, usize, HashSet<ShapeWithGrid>) = prev_polys
-        .par_iter()
-        .fold(
-            || (0, 0, HashSet::<ShapeWithGrid>::new()),
-            |(mut points_tried, mut polys_tried, mut new_polys), prev_poly| {
-                for p in &prev_poly.points {
-                    for m in MOVES32 {
-                        points_tried += 1;
-                        let new_point = p + m;
-                        if prev_poly.points.contains(&new_point) {
-                            continue;
-                        }
+        let poly = S::new(vec![SVector::zeros()]);
+        let polys = if keep(&poly) && sample != Some(0) { HashSet::from([poly]) } else { HashSet::new() };
+        let stats = GenerationStats {
+            size: n,
+            elapsed: start.elapsed(),
+            points_tried: 1,
+            polys_tried: 1,
+            found: polys.len(),
+            profile: None,
+            children: None,
+        };
+        report_performance(&stats);
+        return (polys, stats);
+    }
 
-                        polys_tried += 1;
-                        // cloning then pushing would force an unnecessary grow, so we initialize with the correct size
-                        let mut new_points = Vec::with_capacity(prev_poly.points.len() + 1);
-                        new_points.extend(&prev_poly.points);
-                        new_points.push(new_point);
+    let moves = S::moves();
+    let mut prev_polys: Vec<&S> = known_polys[&(n - 1)].iter().collect();
+    if let Some(order) = options.parent_order {
+        // so that similar parents, which grow into similar polys, are grown close together
+        prev_polys.par_sort_unstable_by(|a, b| order(a, b));
+    }
+    if let Some(shard) = options.shard {
+        assert!(options.parent_order.is_some(), "shards are only the same across runs for sorted parents");
+        prev_polys = prev_polys[shard.range(prev_polys.len())].to_vec();
+    }
+    let new_polys: DashSet<S> = DashSet::new();
+    let points_tried = AtomicUsize::new(0);
+    let polys_tried = AtomicUsize::new(0);
+    // only counted when sampling or showing the status line, to not contend on it otherwise
+    let found = AtomicUsize::new(0);
+    let sampled_enough = || sample.is_some_and(|sample| found.load(Ordering::Relaxed) >= sample);
+    // what the smaller sizes take up is only summed once, the polys of this size are added as they're found
+    let bytes = AtomicUsize::new(match options.budget {
+        Some((_, estimate)) => known_polys.values().flatten().map(estimate).sum(),
+        None => 0,
+    });
+    let over_budget = || options.budget.is_some_and(|(max_bytes, _)| bytes.load(Ordering::Relaxed) > max_bytes);
+    let stop = || sampled_enough() || over_budget();
+    let offer = |new_poly: S| {
+        let new_bytes = options.budget.map_or(0, |(_, estimate)| estimate(&new_poly));
+        if !new_polys.insert(new_poly) {
+            return;
+        }
+        bytes.fetch_add(new_bytes, Ordering::Relaxed);
+        if sample.is_some() || status.is_some() {
+            let found = found.fetch_add(1, Ordering::Relaxed) + 1;
+            if let Some(status) = &status {
+                status.repaint(found);
+            }
+        }
+    };
+    // returns how many distinct children the parent has, if they're counted, or none if the sample or budget cut it
+    // off before all of them were grown
+    let expand = |prev_poly: &&S| -> Option<usize> {
+        if stop() {
+            return None;
+        }
+        let mut cut_off = false;
+        // only filled when counting children, which are offered to the set once they're all known
+        let mut children: HashSet<S> = HashSet::new();
+        // count locally, so the shared counters are only touched once per parent
+        let mut parent_points_tried = 0;
+        let mut parent_polys_tried = 0;
+        let prev_points = prev_poly.points();
+        let prev_leaves = if options.leaf_sites_only { leaves(prev_points, moves) } else { Vec::new() };
+        'points: for p in prev_points {
+            for m in moves {
+                if stop() {
+                    cut_off = true;
+                    break 'points;
+                }
+                parent_points_tried += 1;
+                let new_point = p + m;
+                if prev_points.contains(&new_point) {
+                    continue;
+                }
+                if options.leaf_sites_only && !is_leaf_site(prev_points, &prev_leaves, moves, &new_point) {
+                    continue;
+                }
 
-                        let new_poly = ShapeWithGrid::canonical(new_points);
-                        new_polys.insert(new_poly);
-                    }
+                // cloning then pushing would force an unnecessary grow, so we initialize with the correct size
+                let mut new_points = Vec::with_capacity(prev_points.len() + 1);
+                new_points.extend_from_slice(prev_points);
+                new_points.push(new_point);
+
+                let new_poly = S::new(new_points);
+                parent_polys_tried += 1;
+                if !keep(&new_poly) {
+                    continue;
                 }
-                (points_tried, polys_tried, new_polys)
-            },
-        )
-        .reduce(
-            || (0, 0, HashSet::<ShapeWithGrid>::new()),
-            |mut a, b| {
-                a.2.extend(b.2);
-                (a.0 + b.0, a.1 + b.1, a.2)
-            },
-        );
+                if options.children {
+                    children.insert(new_poly);
+                } else {
+                    offer(new_poly);
+                }
+            }
+        }
+        points_tried.fetch_add(parent_points_tried, Ordering::Relaxed);
+        polys_tried.fetch_add(parent_polys_tried, Ordering::Relaxed);
+        let child_count = children.len();
+        children.into_iter().for_each(offer);
+        (!cut_off).then_some(child_count)
+    };
+    let expand_timed = |(mut times, mut child_counts): (Vec<Duration>, BTreeMap<usize, usize>), prev_poly: &&S| {
+        let start = Instant::now();
+        // parents that were cut off would only skew the profile and pile up as having no children
+        let Some(child_count) = expand(prev_poly) else {
+            return (times, child_counts);
+        };
+        if options.profile {
+            times.push(start.elapsed());
+        }
+        if options.children {
+            *child_counts.entry(child_count).or_insert(0) += 1;
+        }
+        (times, child_counts)
+    };
+    let (parent_times, child_counts): (Vec<Duration>, BTreeMap<usize, usize>) = if prev_polys.len() < options.parallel_threshold {
+        prev_polys.iter().fold((Vec::new(), BTreeMap::new()), expand_timed)
+    } else if options.profile || options.children {
+        prev_polys
+            .par_iter()
+            // buffered per thread, and only merged at the end
+            .fold(|| (Vec::new(), BTreeMap::new()), expand_timed)
+            .reduce(
+                || (Vec::new(), BTreeMap::new()),
+                |(mut times_a, mut child_counts_a), (mut times_b, child_counts_b)| {
+                    times_a.append(&mut times_b);
+                    for (child_count, parents) in child_counts_b {
+                        *child_counts_a.entry(child_count).or_insert(0) += parents;
+                    }
+                    (times_a, child_counts_a)
+                },
+            )
+    } else {
+        prev_polys.par_iter().for_each(|prev_poly| {
+            expand(prev_poly);
+        });
+        (Vec::new(), BTreeMap::new())
+    };
+    let result = (
+        points_tried.into_inner(),
+        polys_tried.into_inner(),
+        // threads may overshoot the sample a little before they notice
+        new_polys.into_iter().take(sample.unwrap_or(usize::MAX)).collect::<HashSet<S>>(),
+    );
 
     let new_polys = result.2;
-    report_performance(start, result.0, result.1, new_polys.len());
-    new_polys
+    let stats = GenerationStats {
+        size: n,
+        elapsed: start.elapsed(),
+        points_tried: result.0,
+        polys_tried: result.1,
+        found: new_polys.len(),
+        profile: ExpansionProfile::of(parent_times),
+        children: options.children.then_some(child_counts),
+    };
+    report_performance(&stats);
+    (new_polys, stats)
+}
+
+// all polys of size target_n that contain the given connected seed shape, by growing from the seed
+// rather than from a single cell
+pub fn generate_extensions(seed: &[Vector2<i32>], target_n: usize) -> HashSet<ShapeWithGrid> {
+    debug_assert!(is_connected(seed), "seed must be connected");
+    if target_n < seed.len() {
+        return HashSet::new();
+    }
+
+    let known_polys = HashMap::from([(seed.len(), HashSet::from([ShapeWithGrid::new(seed.to_vec())]))]);
+    grow_to_size(known_polys, seed.len(), target_n)
+}
+
+// like generate_shapes_up_to_size(n)[&n], but without keeping the smaller sizes around
+pub fn generate_shapes_exactly<S, T>(n: usize) -> HashSet<S>
+where
+    S: ShapeN<T, 2> + Hash + Eq + Send + Sync,
+    T: Scalar + Zero + ClosedAddAssign + Send + Sync,
+{
+    grow_to_size(HashMap::new(), 0, n)
+}
+
+// like generate_shapes_up_to_size(up_to), but picks up from the largest size in known, e.g. as read back from a
+// file, and keeps all sizes it was given. returns known as is if it already reaches up_to
+pub fn continue_generation<S, T>(mut known: HashMap<usize, HashSet<S>>, up_to: usize) -> HashMap<usize, HashSet<S>>
+where
+    S: ShapeN<T, 2> + Hash + Eq + Send + Sync,
+    T: Scalar + Zero + ClosedAddAssign + Send + Sync,
+{
+    let from = known.keys().max().copied().unwrap_or(0);
+    for n in from + 1..=up_to {
+        let (polys, _) = generate_shapes_with_size(n, &known);
+        known.insert(n, polys);
+    }
+    known
+}
+
+// the union of polys grown from separate parts of the same parents, e.g. on different machines. polys are canonical,
+// so the same poly grown in both parts is equal in both and kept once. extends the larger set, to rehash less
+pub fn merge_sets(a: HashSet<ShapeWithGrid>, b: HashSet<ShapeWithGrid>) -> HashSet<ShapeWithGrid> {
+    let (mut larger, smaller) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    larger.extend(smaller);
+    larger
+}
+
+// grows the polys of size from up to size to, dropping each size as soon as the next one is grown
+fn grow_to_size<S, T>(mut known_polys: HashMap<usize, HashSet<S>>, from: usize, to: usize) -> HashSet<S>
+where
+    S: ShapeN<T, 2> + Hash + Eq + Send + Sync,
+    T: Scalar + Zero + ClosedAddAssign + Send + Sync,
+{
+    for n in from + 1..=to {
+        let (polys, _) = grow_shapes_with_size(n, &known_polys, &|_| true, GrowOptions::default());
+        known_polys.remove(&(n - 1));
+        known_polys.insert(n, polys);
+    }
+    known_polys.remove(&to).unwrap_or_default()
 }
 
-fn generate_shape_minimal_up_to(max_n: usize) -> HashMap<usize, HashSet<ShapeMinimal>> {
-    let mut known_polys: HashMap<usize, HashSet<ShapeMinimal>> = HashMap::new();
+// like generate_shapes_up_to_size::<ShapeWithGrid, i32>, but only keeps fingerprints of the canonical grids
+// around, and only of the previous size. saves allocations when only the counts are of interest.
+pub fn count_shapes_up_to_size(max_n: usize) -> Vec<GenerationStats> {
+    let mut stats = Vec::with_capacity(max_n);
+    let mut prev_grids: HashSet<Fingerprint> = HashSet::new();
     for n in 1..=max_n {
-        let polys = generate_shape_minimal_level(n, &known_polys);
-        known_polys.entry(n).or_insert(polys);
+        let (grids, size_stats) = count_shapes_with_size(n, &prev_grids);
+        prev_grids = grids;
+        stats.push(size_stats);
     }
-    known_polys
+    stats
 }
 
-fn generate_shape_minimal_level(
-    n: usize,
-    known_polys: &HashMap<usize, HashSet<ShapeMinimal>>,
-) -> HashSet<ShapeMinimal> {
+fn count_shapes_with_size(n: usize, prev_grids: &HashSet<Fingerprint>) -> (HashSet<Fingerprint>, GenerationStats) {
     let start = Instant::now();
-    print!("size: {: >2}... ", n);
+    report_size_start(n);
 
     if n == 1 {
-        report_performance(start, 1, 1, 1);
-        return HashSet::from([ShapeMinimal::new(vec![Vector2::new(0, 0)])]);
-    }
-
-    let prev_polys: &HashSet<ShapeMinimal> = &known_polys[&(n - 1)];
-    let result: (usize, usize, HashSet<ShapeMinimal>) = prev_polys
-        .par_iter()
-        .fold(
-            || (0, 0, HashSet::<ShapeMinimal>::new()),
-            |(mut points_tried, mut polys_tried, mut new_polys), prev_poly| {
-                for p in &prev_poly.points {
-                    for m in MOVES8 {
-                        points_tried += 1;
-                        let new_point = p + m;
-                        if prev_poly.points.contains(&new_point) {
-                            continue;
-                        }
+        let stats = GenerationStats {
+            size: n,
+            elapsed: start.elapsed(),
+            points_tried: 1,
+            polys_tried: 1,
+            found: 1,
+            profile: None,
+            children: None,
+        };
+        report_performance(&stats);
+        return (HashSet::from([Fingerprint::of(&canonical_grid(&vec![Vector2::zeros()]).1)]), stats);
+    }
 
-                        polys_tried += 1;
-                        // cloning then pushing would force an unnecessary grow, so we initialize with the correct size
-                        let mut new_points = Vec::with_capacity(prev_poly.points.len() + 1);
-                        new_points.extend(&prev_poly.points);
-                        new_points.push(new_point);
+    let new_grids: DashSet<Fingerprint> = DashSet::new();
+    let points_tried = AtomicUsize::new(0);
+    let polys_tried = AtomicUsize::new(0);
+    let status = status_line().then(|| SizeStatus::new(n, start));
+    let found = AtomicUsize::new(0);
+    prev_grids.par_iter().for_each(|prev_grid| {
+        let mut parent_points_tried = 0;
+        let mut parent_polys_tried = 0;
+        let prev_points = grid_points(&prev_grid.grid());
+        // reused across candidates, only the last point differs
+        let mut new_points = Vec::with_capacity(prev_points.len() + 1);
+        for p in &prev_points {
+            for m in MOVES32 {
+                parent_points_tried += 1;
+                let new_point = p + m;
+                if prev_points.contains(&new_point) {
+                    continue;
+                }
+
+                parent_polys_tried += 1;
+                new_points.clear();
+                new_points.extend_from_slice(&prev_points);
+                new_points.push(new_point);
 
-                        let new_poly = ShapeMinimal::new(new_points).canonical_clone_with_grid(ROTATIONS8);
-                        new_polys.insert(new_poly);
+                if new_grids.insert(Fingerprint::of(&canonical_grid(&new_points).1)) {
+                    if let Some(status) = &status {
+                        status.repaint(found.fetch_add(1, Ordering::Relaxed) + 1);
                     }
                 }
-                (points_tried, polys_tried, new_polys)
-            },
-        )
-        .reduce(
-            || (0, 0, HashSet::<ShapeMinimal>::new()),
-            |mut a, b| {
-                a.2.extend(b.2);
-                (a.0 + b.0, a.1 + b.1, a.2)
-            },
-        );
+            }
+        }
+        points_tried.fetch_add(parent_points_tried, Ordering::Relaxed);
+        polys_tried.fetch_add(parent_polys_tried, Ordering::Relaxed);
+    });
+    let result = (
+        points_tried.into_inner(),
+        polys_tried.into_inner(),
+        new_grids.into_iter().collect::<HashSet<Fingerprint>>(),
+    );
 
-    let new_polys = result.2;
-    report_performance(start, result.0, result.1, new_polys.len());
-    new_polys
+    let new_grids = result.2;
+    let stats = GenerationStats {
+        size: n,
+        elapsed: start.elapsed(),
+        points_tried: result.0,
+        polys_tried: result.1,
+        found: new_grids.len(),
+        profile: None,
+        children: None,
+    };
+    report_performance(&stats);
+    (new_grids, stats)
 }
 
-fn report_performance(start: Instant, points_tried: usize, polys_tried: usize, found: usize) {
-    let dur = start.elapsed();
+// printed unless quiet. without a newline, so the size being generated shows up before it's done
+pub(crate) fn report_progress(args: std::fmt::Arguments) {
+    if verbosity() >= Verbosity::Normal {
+        status_line::finish();
+        print!("{}", args);
+        // stdout is line buffered
+        io::stdout().flush().ok();
+    }
+}
+
+// the status line shows the size along with everything else, once there's something to show
+pub(crate) fn report_size_start(n: usize) {
+    if !status_line() {
+        report_progress(format_args!("size: {: >2}... ", n));
+    }
+}
+
+pub(crate) fn report_performance(stats: &GenerationStats) {
+    if verbosity() < Verbosity::Normal {
+        return;
+    }
+    let dur = stats.elapsed;
+    let points_tried = stats.points_tried;
+    let polys_tried = stats.polys_tried;
+    let found = stats.found;
 
     let points_tried_string = format!(
         "points tried: {: >10} {: >12}",
@@ -187,20 +915,875 @@ fn report_performance(start: Instant, points_tried: usize, polys_tried: usize, f
         )
     );
 
-    println!(
-        "time: {}s {: <40} {: <40} {: <40}",
+    let performance = format!(
+        "time: {}s {: <40} {: <40} {: <40} {}",
         dur.as_secs(),
         points_tried_string,
         polys_tried_string,
-        found_string
+        found_string,
+        overcount_string(stats)
     );
+    if status_line() {
+        status_line::paint(format_args!("size: {: >2}... {}", stats.size, performance));
+    } else {
+        println!("{}", performance);
+    }
 }
 
-fn report_polys(cli: Poly2d, known_polys: HashMap<usize, HashSet<ShapeWithGrid>>) {
-    for n in 1..=cli.max_n {
-        println!("Polys with size n={}", n);
-        for poly in &known_polys[&n] {
-            println!("{}", &poly);
+fn overcount_string(stats: &GenerationStats) -> String {
+    let overcount = stats.overcount().map_or("-".to_string(), |overcount| NUM.format(".3s", overcount));
+    format!("tried per found: {: >6}", overcount)
+}
+
+fn report_polys(
+    max_n: usize,
+    format: Format,
+    style: DisplayStyle,
+    limit: Option<usize>,
+    known_polys: &HashMap<usize, HashSet<ShapeWithGrid>>,
+) {
+    // stdout is line buffered, so this can only fail if stdout is gone, in which case println! would panic too
+    write_polys(&mut io::stdout().lock(), max_n, format, style, limit, known_polys).expect("failed to write to stdout");
+}
+
+fn write_polys_to_file(
+    path: &Path,
+    max_n: usize,
+    format: Format,
+    style: DisplayStyle,
+    known_polys: &HashMap<usize, HashSet<ShapeWithGrid>>,
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_polys(&mut writer, max_n, format, style, None, known_polys)?;
+    // dropping a BufWriter swallows errors, so flush explicitly
+    writer.flush()
+}
+
+// one file per size, in the same format as write_polys_to_file, so diff and classify can read each of them. a size
+// that can't be written doesn't stop the others, but the first error is returned once all have been tried
+fn write_polys_to_dir(
+    dir: &Path,
+    max_n: usize,
+    format: Format,
+    style: DisplayStyle,
+    known_polys: &HashMap<usize, HashSet<ShapeWithGrid>>,
+) -> Result<(), PolyError> {
+    fs::create_dir_all(dir).map_err(|e| PolyError::Write(dir.to_path_buf(), e))?;
+    let mut first_error = None;
+    for n in 1..=max_n {
+        let path = dir.join(format!("size-{}.txt", n));
+        let written = File::create(&path).and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            write_polys_of_size(&mut writer, n, format, style, None, &known_polys[&n])?;
+            writer.flush()
+        });
+        if let Err(e) = written {
+            first_error.get_or_insert(PolyError::Write(path, e));
+        }
+    }
+    first_error.map_or(Ok(()), Err)
+}
+
+pub(crate) fn write_polys(
+    out: &mut impl Write,
+    max_n: usize,
+    format: Format,
+    style: DisplayStyle,
+    limit: Option<usize>,
+    known_polys: &HashMap<usize, HashSet<ShapeWithGrid>>,
+) -> io::Result<()> {
+    for n in 1..=max_n {
+        write_polys_of_size(out, n, format, style, limit, &known_polys[&n])?;
+    }
+    Ok(())
+}
+
+fn write_polys_of_size(
+    out: &mut impl Write,
+    n: usize,
+    format: Format,
+    style: DisplayStyle,
+    limit: Option<usize>,
+    polys: &HashSet<ShapeWithGrid>,
+) -> io::Result<()> {
+    writeln!(out, "Polys with size n={}", n)?;
+    // sorted, so that output is the same on every run
+    let mut sorted: Vec<&ShapeWithGrid> = polys.iter().collect();
+    sorted.sort();
+    if let Some(limit) = limit {
+        sorted.truncate(limit);
+    }
+    for poly in &sorted {
+        match format {
+            Format::Ascii => writeln!(out, "{}", Styled(poly, style))?,
+            Format::Rle => writeln!(out, "{}", poly.to_rle())?,
+            Format::Compact => writeln!(out, "{}", poly.to_compact())?,
+            Format::Coords => writeln!(out, "{}", poly.to_coords())?,
         }
     }
+    if sorted.len() < polys.len() {
+        writeln!(out, "... and {} more", polys.len() - sorted.len())?;
+    }
+    Ok(())
+}
+
+// fails if two polys of a size are rotations of each other, or if the polys of a size up to complete_n aren't as many
+// as Burnside's lemma says there are for the fixed polys of that size, which are generated separately for it
+fn self_check(
+    max_n: usize,
+    complete_n: usize,
+    known_polys: &HashMap<usize, HashSet<ShapeWithGrid>>,
+) -> Result<(), PolyError> {
+    let fixed = generate_shapes_up_to_size::<ShapeFixed, i32>(complete_n);
+    for n in 1..=max_n {
+        let duplicates = rotated_duplicates(&known_polys[&n]);
+        for (a, b) in &duplicates {
+            eprintln!("rotations of each other:\n{}\n{}", a.canonical_dump(), b.canonical_dump());
+        }
+        if !duplicates.is_empty() {
+            return Err(PolyError::SelfCheck(n));
+        }
+        if n <= complete_n {
+            let multiplicities =
+                fixed[&n].iter().map(|poly| ShapeWithGrid::new(poly.points().clone()).rotation_multiplicity());
+            check_burnside(n, multiplicities, &known_polys[&n])?;
+        }
+    }
+    Ok(())
+}
+
+fn report_canonical(max_n: usize, known_polys: &HashMap<usize, HashSet<ShapeWithGrid>>) {
+    write_canonical(&mut io::stdout().lock(), max_n, known_polys).expect("failed to write to stdout");
+}
+
+fn write_canonical(
+    out: &mut impl Write,
+    max_n: usize,
+    known_polys: &HashMap<usize, HashSet<ShapeWithGrid>>,
+) -> io::Result<()> {
+    for n in 1..=max_n {
+        writeln!(out, "Canonical grids with size n={}", n)?;
+        let mut polys: Vec<&ShapeWithGrid> = known_polys[&n].iter().collect();
+        polys.sort();
+        for poly in polys {
+            writeln!(out, "{}", poly.canonical_dump())?;
+        }
+    }
+    Ok(())
+}
+
+fn report_profile(stats: &[GenerationStats]) {
+    println!("{: >4} {: >10} {: >12} {: >12} {: >12}", "n", "parents", "min", "median", "max");
+    for s in stats {
+        if let Some(profile) = &s.profile {
+            println!(
+                "{: >4} {: >10} {: >12} {: >12} {: >12}",
+                s.size,
+                profile.parents,
+                format!("{:?}", profile.min),
+                format!("{:?}", profile.median),
+                format!("{:?}", profile.max)
+            );
+        }
+    }
+}
+
+fn report_children(stats: &[GenerationStats]) {
+    println!("{: >4} {: >10} {: >10}", "n", "children", "parents");
+    for s in stats {
+        for (children, parents) in s.children.iter().flatten() {
+            println!("{: >4} {: >10} {: >10}", s.size, children, parents);
+        }
+    }
+}
+
+fn report_cumulative(stats: &[GenerationStats]) {
+    println!("{: >4} {: >16} {: >16}", "n", "count", "cumulative");
+    for (n, count, cumulative) in cumulative_counts(stats) {
+        println!(
+            "{: >4} {: >16} {: >16}",
+            n,
+            NUM.format(",d", count as f64),
+            NUM.format(",d", cumulative as f64)
+        );
+    }
+}
+
+fn report_growth_ratios(stats: &[GenerationStats]) {
+    println!("{: >4} {: >10}", "n", "ratio");
+    let ratios = growth_ratios(stats);
+    for (n, ratio) in &ratios {
+        println!("{: >4} {: >10.4}", n, ratio);
+    }
+    if let Some((_, ratio)) = ratios.last() {
+        println!("growth constant estimate: {:.4}", ratio);
+    }
+}
+
+// (n, count of size n / count of size n - 1), which approaches the growth constant of the polys as n grows. sizes
+// following one without any polys are left out
+fn growth_ratios(stats: &[GenerationStats]) -> Vec<(usize, f64)> {
+    stats
+        .windows(2)
+        .filter(|pair| pair[0].found > 0)
+        .map(|pair| (pair[1].size, pair[1].found as f64 / pair[0].found as f64))
+        .collect()
+}
+
+// (n, count, count of all polys up to size n)
+fn cumulative_counts(stats: &[GenerationStats]) -> Vec<(usize, usize, usize)> {
+    stats
+        .iter()
+        .scan(0, |cumulative, s| {
+            *cumulative += s.found;
+            Some((s.size, s.found, *cumulative))
+        })
+        .collect()
+}
+
+fn write_csv_to_file(path: &Path, stats: &[GenerationStats]) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_csv(&mut writer, stats)?;
+    writer.flush()
+}
+
+fn write_csv(out: &mut impl Write, stats: &[GenerationStats]) -> io::Result<()> {
+    writeln!(out, "n,found,points_tried,polys_tried,seconds")?;
+    for s in stats {
+        writeln!(out, "{},{},{},{},{}", s.size, s.found, s.points_tried, s.polys_tried, s.elapsed.as_secs_f64())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, HashMap, HashSet};
+    use std::path::Path;
+    use std::time::Duration;
+
+    use nalgebra::Vector2;
+
+    use crate::cli::{Algorithm, Format, Shard};
+    use crate::poly_2d::config::GenerationConfig;
+    use crate::poly_2d::poly::{
+        compare_algorithms, continue_generation, count_shapes_up_to_size, cumulative_counts, disagreements,
+        generate_budgeted_shapes_up_to_size, generate_extensions, generate_filtered_shapes_up_to_size, generate_polys,
+        generate_sampled_shapes_up_to_size, generate_sampled_shapes_up_to_size_sequential, generate_shapes_exactly,
+        generate_shapes_up_to_size, generate_shapes_up_to_size_sequential, generate_shapes_up_to_size_while,
+        generate_shapes_up_to_size_with_stats, generate_shapes_with_size, generate_shard_with_size,
+        grow_shapes_with_size, growth_ratios, is_leaf_site, leaves, merge_sets, overcount_string, self_check,
+        total_count, write_canonical, write_csv_to_file, write_polys, write_polys_to_dir, write_polys_to_file,
+        GenerationStats, GrowOptions, NUM, PARALLEL_THRESHOLD,
+    };
+    use crate::poly_2d::poly_error::PolyError;
+    use crate::poly_2d::rotation::ROTATIONS32;
+    use crate::poly_2d::shape::grid::DisplayStyle;
+    use crate::poly_2d::shape::shape_generic::ShapeN;
+    use crate::poly_2d::shape::shape_minimal::ShapeMinimal;
+    use crate::poly_2d::shape::shape_with_grid::ShapeWithGrid;
+    use crate::poly_2d::symmetry::to_free;
+
+    // whether both representations consider the two point sets to be the same shape
+    fn representations_agree(a: &[(i8, i8)], b: &[(i8, i8)]) -> bool {
+        let grid = |points: &[(i8, i8)]| {
+            ShapeWithGrid::new(points.iter().map(|&(x, y)| Vector2::new(x as i32, y as i32)).collect())
+        };
+        let minimal = |points: &[(i8, i8)]| {
+            ShapeMinimal::new(points.iter().map(|&(x, y)| Vector2::new(x, y)).collect())
+        };
+        (grid(a) == grid(b)) == (minimal(a) == minimal(b))
+    }
+
+    #[test]
+    fn should_agree_on_rotations_across_representations() {
+        // L tetromino, and the same rotated by 90 degrees with points listed in a different order
+        let l = [(0, 0), (0, 1), (0, 2), (1, 0)];
+        let l_rotated = [(2, 1), (0, 0), (2, 0), (1, 0)];
+        assert!(representations_agree(&l, &l_rotated));
+        assert_eq!(
+            ShapeMinimal::new(l.iter().map(|&(x, y)| Vector2::new(x, y)).collect()),
+            ShapeMinimal::new(l_rotated.iter().map(|&(x, y)| Vector2::new(x, y)).collect())
+        );
+
+        // S and Z tetrominoes are mirror images, not rotations
+        let s = [(1, 0), (2, 0), (0, 1), (1, 1)];
+        let z = [(0, 0), (1, 0), (1, 1), (2, 1)];
+        assert!(representations_agree(&s, &z));
+    }
+
+    #[test]
+    fn should_return_stats_per_size() {
+        let (known_polys, stats) = generate_shapes_up_to_size_with_stats::<ShapeWithGrid, i32>(6);
+        assert_eq!(stats.len(), 6);
+        for (i, s) in stats.iter().enumerate() {
+            assert_eq!(s.size, i + 1);
+            assert_eq!(s.found, known_polys[&s.size].len());
+            assert!(s.points_tried >= s.polys_tried);
+            assert!(s.polys_tried >= s.found);
+        }
+        // the 2 trominoes each have 3 points with 4 neighbors each, 4 of which are occupied
+        assert_eq!(stats[3].points_tried, 24);
+        assert_eq!(stats[3].polys_tried, 16);
+    }
+
+    #[test]
+    fn should_only_keep_straight_polys_in_1_wide_box() {
+        let (known_polys, _) = generate_filtered_shapes_up_to_size::<ShapeWithGrid, i32>(6, &|poly| poly.fits_in(1, 6));
+        for n in 1..=6 {
+            let polys = &known_polys[&n];
+            assert_eq!(polys.len(), 1, "n={}", n);
+            assert_eq!(polys.iter().next().unwrap(), &ShapeWithGrid::new((0..n as i32).map(|y| Vector2::new(0, y)).collect()));
+        }
+    }
+
+    #[test]
+    fn should_only_keep_polys_fitting_in_2x2_box() {
+        let (known_polys, _) = generate_filtered_shapes_up_to_size::<ShapeWithGrid, i32>(5, &|poly| poly.fits_in(2, 2));
+        let counts = (1..=5).map(|n| known_polys[&n].len()).collect::<Vec<_>>();
+        // single cell, domino, L tromino, square
+        assert_eq!(counts, vec![1, 1, 1, 1, 0]);
+    }
+
+    #[test]
+    fn should_generate_the_same_with_default_config() {
+        let stats = generate_polys(GenerationConfig { max_n: 6, ..Default::default() }).unwrap();
+        let (_, expected) = generate_shapes_up_to_size_with_stats::<ShapeWithGrid, i32>(6);
+        assert_eq!(GenerationConfig::default().algorithm, Algorithm::A32);
+        assert_eq!(stats.len(), 6);
+        for (actual, expected) in stats.iter().zip(&expected) {
+            assert_eq!(
+                (actual.size, actual.points_tried, actual.polys_tried, actual.found),
+                (expected.size, expected.points_tried, expected.polys_tried, expected.found)
+            );
+        }
+    }
+
+    #[test]
+    fn should_sample_at_most_k_polys_per_size() {
+        let all = generate_shapes_up_to_size::<ShapeWithGrid, i32>(8);
+        let sampled = generate_sampled_shapes_up_to_size::<ShapeWithGrid, i32>(8, 5);
+        for n in 1..=8 {
+            assert!(!sampled[&n].is_empty(), "n={}", n);
+            assert!(sampled[&n].len() <= 5, "n={}", n);
+            assert!(sampled[&n].is_subset(&all[&n]), "n={}", n);
+        }
+        // sizes with fewer polys than the sample are complete
+        assert_eq!(sampled[&3], all[&3]);
+    }
+
+    #[test]
+    fn should_generate_the_same_sequentially() {
+        let parallel = generate_shapes_up_to_size::<ShapeWithGrid, i32>(8);
+        let sequential = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(8);
+        assert_eq!(sequential, parallel);
+    }
+
+    #[test]
+    fn should_sample_the_same_polys_sequentially() {
+        let sample = || generate_sampled_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(8, 5);
+        let sampled = sample();
+        assert_eq!(sampled, sample());
+        // no threads to overshoot the sample, so sizes with enough polys have exactly the sample
+        for n in 4..=8 {
+            assert_eq!(sampled[&n].len(), 5, "n={}", n);
+        }
+    }
+
+    #[test]
+    fn should_generate_the_same_across_parallel_threshold() {
+        let with_threshold = |parallel_threshold| {
+            let options = GrowOptions { parallel_threshold, ..Default::default() };
+            generate_shapes_up_to_size_while::<ShapeWithGrid, i32, 2>(9, &|_| true, options, &|_| true).0
+        };
+        let parallel = with_threshold(0);
+        // sizes up to 7 grow from fewer than 196 parents; 8 from exactly 196, 9 from 704
+        assert_eq!(with_threshold(196), parallel);
+        assert_eq!(with_threshold(PARALLEL_THRESHOLD), parallel);
+        assert_eq!(with_threshold(usize::MAX), parallel);
+    }
+
+    #[test]
+    fn should_merge_polys_grown_from_halves_of_the_parents() {
+        let mut known = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(8);
+        let mut parents: Vec<ShapeWithGrid> = known.remove(&7).unwrap().into_iter().collect();
+        parents.sort();
+        let second_half: HashSet<ShapeWithGrid> = parents.split_off(parents.len() / 2).into_iter().collect();
+        let first_half: HashSet<ShapeWithGrid> = parents.into_iter().collect();
+
+        let grow = |half| generate_shapes_with_size(8, &HashMap::from([(7, half)])).0;
+        let (a, b) = (grow(first_half), grow(second_half));
+        // some polys of size 8 grow from parents in both halves
+        assert!(a.len() + b.len() > known[&8].len());
+        assert_eq!(merge_sets(a, b), known[&8]);
+    }
+
+    #[test]
+    fn should_merge_shards_into_all_polys() {
+        let mut known = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(8);
+        let all = known.remove(&8).unwrap();
+        let shards: Vec<HashSet<ShapeWithGrid>> =
+            (1..=3).map(|index| generate_shard_with_size(8, &known, Shard { index, count: 3 }).0).collect();
+        assert!(shards.iter().all(|shard| !shard.is_empty() && shard.len() < all.len()));
+        assert_eq!(shards.into_iter().reduce(merge_sets).unwrap(), all);
+    }
+
+    #[test]
+    fn should_only_shard_the_largest_size() {
+        let options = GrowOptions {
+            parent_order: Some(ShapeWithGrid::cmp as fn(&_, &_) -> _),
+            shard: Some(Shard { index: 2, count: 3 }),
+            ..Default::default()
+        };
+        let (sharded, _) = generate_shapes_up_to_size_while::<ShapeWithGrid, i32, 2>(8, &|_| true, options, &|_| true);
+        let all = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(8);
+        for n in 1..8 {
+            assert_eq!(sharded[&n], all[&n], "n={}", n);
+        }
+        assert!(sharded[&8].is_subset(&all[&8]) && sharded[&8].len() < all[&8].len());
+    }
+
+    #[test]
+    fn should_generate_the_same_with_sorted_parents() {
+        let options = GrowOptions { parent_order: Some(ShapeWithGrid::cmp as fn(&_, &_) -> _), ..Default::default() };
+        let (sorted, _) = generate_shapes_up_to_size_while::<ShapeWithGrid, i32, 2>(8, &|_| true, options, &|_| true);
+        assert_eq!(sorted, generate_shapes_up_to_size::<ShapeWithGrid, i32>(8));
+    }
+
+    #[test]
+    fn should_generate_the_same_from_leaf_sites_only() {
+        let options = GrowOptions { leaf_sites_only: true, ..Default::default() };
+        let (from_leaves, stats) = generate_shapes_up_to_size_while(10, &|_| true, options, &|_| true);
+        let (all, all_stats) = generate_shapes_up_to_size_with_stats::<ShapeWithGrid, i32>(10);
+        assert_eq!(from_leaves, all);
+        for (s, all_s) in stats.iter().zip(&all_stats) {
+            assert_eq!(s.found, all_s.found, "n={}", s.size);
+            assert!(s.polys_tried <= all_s.polys_tried, "n={}", s.size);
+        }
+        // no tromino has a site with more than one neighbor that leaves a leaf behind, but from the tetrominoes on
+        // fewer candidates are built for the same polys
+        for n in 5..=10 {
+            assert!(stats[n - 1].polys_tried < all_stats[n - 1].polys_tried, "n={}", n);
+        }
+    }
+
+    #[test]
+    fn should_only_accept_leaf_sites_unless_child_has_no_leaves() {
+        let moves = ShapeWithGrid::moves();
+        // an L tromino, whose leaves are its ends
+        let l = [Vector2::new(0, 0), Vector2::new(1, 0), Vector2::new(0, 1)];
+        let l_leaves = leaves(&l, moves);
+        assert_eq!(l_leaves, [Vector2::new(1, 0), Vector2::new(0, 1)]);
+        // a single neighbor
+        assert!(is_leaf_site(&l, &l_leaves, moves, &Vector2::new(2, 0)));
+        // closes the square, which has no leaves
+        assert!(is_leaf_site(&l, &l_leaves, moves, &Vector2::new(1, 1)));
+        // an L tetromino, where filling the corner leaves the end of the long arm a leaf of the P pentomino, which is
+        // grown from the square instead
+        let l = [Vector2::new(0, 0), Vector2::new(1, 0), Vector2::new(0, 1), Vector2::new(0, 2)];
+        assert!(!is_leaf_site(&l, &leaves(&l, moves), moves, &Vector2::new(1, 1)));
+    }
+
+    #[test]
+    fn should_profile_each_parent() {
+        let stats = generate_polys(GenerationConfig { max_n: 6, profile: true, ..Default::default() }).unwrap();
+        assert_eq!(stats[0].profile, None);
+        for pair in stats.windows(2) {
+            let profile = pair[1].profile.as_ref().unwrap();
+            assert_eq!(profile.parents, pair[0].found);
+            assert!(profile.min <= profile.median && profile.median <= profile.max);
+        }
+        let unprofiled = generate_polys(GenerationConfig { max_n: 6, ..Default::default() }).unwrap();
+        assert!(unprofiled.iter().all(|s| s.profile.is_none()));
+    }
+
+    #[test]
+    fn should_sum_counts_cumulatively() {
+        let (_, stats) = generate_shapes_up_to_size_with_stats::<ShapeWithGrid, i32>(5);
+        assert_eq!(
+            cumulative_counts(&stats),
+            vec![(1, 1, 1), (2, 1, 2), (3, 2, 4), (4, 7, 11), (5, 18, 29)]
+        );
+    }
+
+    #[test]
+    fn should_count_polys_of_all_sizes() {
+        let known_polys = generate_shapes_up_to_size::<ShapeWithGrid, i32>(4);
+        assert_eq!(total_count(&known_polys), 11);
+
+        // https://oeis.org/A000105, free rather than one sided
+        let free = known_polys.iter().map(|(n, polys)| (*n, to_free(polys))).collect();
+        assert_eq!(total_count(&free), 1 + 1 + 2 + 5);
+        assert_eq!(total_count::<ShapeWithGrid>(&HashMap::new()), 0);
+    }
+
+    #[test]
+    fn should_report_polys_tried_per_found() {
+        let stats = |polys_tried, found| GenerationStats {
+            size: 3,
+            elapsed: Duration::from_millis(1),
+            points_tried: 8,
+            polys_tried,
+            found,
+            profile: None,
+            children: None,
+        };
+        // the domino grows into 6 trominoes, of which 2 are distinct
+        assert_eq!(stats(6, 2).overcount(), Some(3.0));
+        assert_eq!(overcount_string(&stats(6, 2)), "tried per found:   3.00");
+        assert_eq!(overcount_string(&stats(3028, 704)), "tried per found:   4.30");
+        assert_eq!(stats(0, 0).overcount(), None);
+        assert_eq!(overcount_string(&stats(0, 0)), "tried per found:      -");
+    }
+
+    #[test]
+    fn should_format_with_thousands_separators() {
+        assert_eq!(NUM.format(",d", 1234567.0), "1,234,567");
+    }
+
+    #[test]
+    fn should_grow_single_size_from_seeded_map() {
+        // only the trominoes, straight and bent, without any smaller sizes
+        let straight = ShapeWithGrid::new(vec![Vector2::new(0, 0), Vector2::new(1, 0), Vector2::new(2, 0)]);
+        let bent = ShapeWithGrid::new(vec![Vector2::new(0, 0), Vector2::new(1, 0), Vector2::new(0, 1)]);
+        let known_polys = HashMap::from([(3, HashSet::from([straight, bent]))]);
+
+        let (polys, stats) = generate_shapes_with_size::<ShapeWithGrid, i32>(4, &known_polys);
+        assert_eq!(polys, generate_shapes_up_to_size::<ShapeWithGrid, i32>(4).remove(&4).unwrap());
+        assert_eq!(stats.size, 4);
+        assert_eq!(stats.found, 7);
+
+        let (polys, _) = generate_shapes_with_size::<ShapeWithGrid, i32>(1, &HashMap::new());
+        assert_eq!(polys.len(), 1);
+    }
+
+    #[test]
+    fn should_continue_generation_from_largest_size() {
+        let direct = generate_shapes_up_to_size::<ShapeWithGrid, i32>(8);
+        let continued = continue_generation(generate_shapes_up_to_size::<ShapeWithGrid, i32>(5), 8);
+        assert_eq!(continued, direct);
+
+        // nothing to do if it's already big enough, and everything if it's empty
+        assert_eq!(continue_generation(generate_shapes_up_to_size::<ShapeWithGrid, i32>(5), 3).len(), 5);
+        assert_eq!(continue_generation::<ShapeWithGrid, i32>(HashMap::new(), 4), generate_shapes_up_to_size(4));
+    }
+
+    #[test]
+    #[should_panic(expected = "polys of size 3 are needed to grow size 4")]
+    fn should_not_grow_single_size_without_previous_size() {
+        let known_polys: HashMap<usize, HashSet<ShapeWithGrid>> = HashMap::from([(2, HashSet::new())]);
+        generate_shapes_with_size::<ShapeWithGrid, i32>(4, &known_polys);
+    }
+
+    #[test]
+    fn should_generate_nothing_for_size_0() {
+        assert!(count_shapes_up_to_size(0).is_empty());
+        assert!(generate_shapes_up_to_size::<ShapeWithGrid, i32>(0).is_empty());
+        assert!(generate_shapes_up_to_size::<ShapeMinimal, i8>(0).is_empty());
+        assert!(generate_shapes_exactly::<ShapeWithGrid, i32>(0).is_empty());
+
+        // every report and file is skipped, rather than indexing sizes that don't exist
+        let config = GenerationConfig {
+            report_polys: true,
+            symmetry_classes: true,
+            metrics: true,
+            cumulative: true,
+            ..Default::default()
+        };
+        assert!(generate_polys(config.clone()).unwrap().is_empty());
+        // only the reports on counts apply when counting
+        let count_only = GenerationConfig { count_only: true, cumulative: true, ..Default::default() };
+        assert!(generate_polys(count_only).unwrap().is_empty());
+    }
+
+    #[test]
+    fn should_count_distinct_children_per_parent() {
+        let stats = generate_polys(GenerationConfig { max_n: 6, children_histogram: true, ..Default::default() }).unwrap();
+        assert_eq!(stats[0].children, None);
+        // the cell only grows into the domino, in whichever direction
+        assert_eq!(stats[1].children, Some(BTreeMap::from([(1, 1)])));
+        // the domino grows into both trominoes
+        assert_eq!(stats[2].children, Some(BTreeMap::from([(2, 1)])));
+        for n in 2..=6 {
+            let children = stats[n - 1].children.as_ref().unwrap();
+            assert_eq!(children.values().sum::<usize>(), stats[n - 2].found, "n={}", n);
+            // every child has at least one parent, and most have several
+            assert!(children.iter().map(|(c, p)| c * p).sum::<usize>() >= stats[n - 1].found, "n={}", n);
+        }
+
+        let stats = generate_polys(GenerationConfig { max_n: 3, ..Default::default() }).unwrap();
+        assert!(stats.iter().all(|s| s.children.is_none()));
+    }
+
+    #[test]
+    fn should_leave_parents_cut_off_by_sample_out_of_children_histogram() {
+        let options = GrowOptions { sample: Some(5), children: true, parallel_threshold: usize::MAX, ..Default::default() };
+        let (_, stats) = generate_shapes_up_to_size_while::<ShapeWithGrid, i32, 2>(7, &|_| true, options, &|_| true);
+        let children = stats[6].children.as_ref().unwrap();
+        // every polyomino has at least one child, so a 0 could only come from parents that weren't grown
+        assert_eq!(children.get(&0), None);
+        assert!(children.values().sum::<usize>() < stats[5].found, "{:?}", children);
+    }
+
+    #[test]
+    fn should_count_more_polys_without_canonicalizing() {
+        let found = |algorithm| -> Vec<usize> {
+            let stats = generate_polys(GenerationConfig { max_n: 7, algorithm, count_only: true, ..Default::default() });
+            stats.unwrap().iter().map(|s| s.found).collect()
+        };
+        let (raw, canonical) = (found(Algorithm::Fixed), found(Algorithm::A32));
+        // from the domino on, some polys have rotations that differ, and those are counted separately
+        for n in 2..=7 {
+            assert!(raw[n - 1] > canonical[n - 1], "n={}", n);
+        }
+        assert!(raw.iter().zip(&canonical).all(|(raw, canonical)| raw >= canonical));
+    }
+
+    #[test]
+    fn should_approach_growth_constant_from_below() {
+        let stats = generate_polys(GenerationConfig { max_n: 9, algorithm: Algorithm::Fixed, ..Default::default() }).unwrap();
+        let ratios = growth_ratios(&stats);
+        assert_eq!(ratios.len(), 8);
+        assert_eq!(ratios[0], (2, 2.0));
+        // fixed polyominoes grow by a factor of about 4.06 in the limit, and the ratios only get there slowly
+        assert!(ratios.windows(2).all(|pair| pair[0].1 < pair[1].1), "{:?}", ratios);
+        let (_, last) = ratios.last().unwrap();
+        assert!(*last > 3.0 && *last < 4.07, "{}", last);
+    }
+
+    #[test]
+    fn should_write_one_file_per_size() {
+        let dir = std::env::temp_dir().join("polycubes_should_write_one_file_per_size");
+        let _ = std::fs::remove_dir_all(&dir);
+        let config = GenerationConfig {
+            max_n: 6,
+            format: Format::Compact,
+            output_dir: Some(dir.clone()),
+            ..Default::default()
+        };
+        generate_polys(config).unwrap();
+
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 6);
+        for (n, count) in [(1, 1), (2, 1), (3, 2), (4, 7), (5, 18), (6, 60)] {
+            let written = std::fs::read_to_string(dir.join(format!("size-{}.txt", n))).unwrap();
+            let lines = written.lines().collect::<Vec<_>>();
+            assert_eq!(lines[0], format!("Polys with size n={}", n));
+            assert_eq!(lines.len() - 1, count, "n={}", n);
+        }
+    }
+
+    #[test]
+    fn should_write_other_sizes_when_one_is_unwritable() {
+        let dir = std::env::temp_dir().join("polycubes_should_write_other_sizes_when_one_is_unwritable");
+        let _ = std::fs::remove_dir_all(&dir);
+        // a directory where the file for size 2 should go
+        std::fs::create_dir_all(dir.join("size-2.txt")).unwrap();
+        let polys = generate_shapes_up_to_size::<ShapeWithGrid, i32>(3);
+
+        let result = write_polys_to_dir(&dir, 3, Format::Compact, DisplayStyle::default(), &polys);
+        assert!(matches!(result, Err(PolyError::Write(path, _)) if path == dir.join("size-2.txt")));
+        assert!(dir.join("size-1.txt").is_file());
+        assert!(dir.join("size-3.txt").is_file());
+    }
+
+    #[test]
+    fn should_agree_between_algorithms() {
+        assert!(compare_algorithms(8).is_ok());
+        let config = GenerationConfig { max_n: 8, compare_algorithms: true, ..Default::default() };
+        assert_eq!(generate_polys(config).unwrap().len(), 8);
+    }
+
+    #[test]
+    fn should_list_sizes_where_algorithms_disagree() {
+        assert!(disagreements(&[1, 1, 2, 5], &[1, 1, 2, 5]).is_empty());
+        assert_eq!(disagreements(&[1, 1, 2, 7, 18], &[1, 1, 3, 7, 17]), vec![(3, 2, 3), (5, 18, 17)]);
+    }
+
+    #[test]
+    fn should_write_csv() {
+        let (_, stats) = generate_shapes_up_to_size_with_stats::<ShapeWithGrid, i32>(5);
+        let path = std::env::temp_dir().join("polycubes_should_write_csv.csv");
+        write_csv_to_file(&path, &stats).unwrap();
+
+        let csv = std::fs::read_to_string(&path).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "n,found,points_tried,polys_tried,seconds");
+        assert_eq!(lines.len(), 6);
+        assert!(lines[4].starts_with("4,7,"));
+
+        // every poly found was tried at least once
+        for line in &lines[1..] {
+            let columns = line.split(',').collect::<Vec<_>>();
+            assert_eq!(columns.len(), 5, "{}", line);
+            let found: usize = columns[1].parse().unwrap();
+            let points_tried: usize = columns[2].parse().unwrap();
+            let polys_tried: usize = columns[3].parse().unwrap();
+            assert!(polys_tried >= found, "{}", line);
+            assert!(points_tried >= polys_tried, "{}", line);
+            assert!(columns[4].parse::<f64>().unwrap() >= 0.0, "{}", line);
+        }
+    }
+
+    // whether some rotation and translation of the seed lies within the points
+    fn contains_rotation_of(points: &[Vector2<i32>], seed: &[Vector2<i32>]) -> bool {
+        ROTATIONS32.iter().any(|rotation| {
+            let rotated = seed.iter().map(|p| rotation * p).collect::<Vec<_>>();
+            points.iter().any(|anchor| {
+                let offset = anchor - rotated[0];
+                rotated.iter().all(|p| points.contains(&(p + offset)))
+            })
+        })
+    }
+
+    #[test]
+    fn should_generate_extensions_of_seed() {
+        let l_tromino = [Vector2::new(0, 0), Vector2::new(1, 0), Vector2::new(0, 1)];
+        let extensions = generate_extensions(&l_tromino, 5);
+
+        // every pentomino except the straight one has a bend
+        assert_eq!(extensions.len(), 17);
+        for poly in &extensions {
+            assert_eq!(poly.points.len(), 5);
+            assert!(contains_rotation_of(&poly.points, &l_tromino), "{}", poly);
+        }
+    }
+
+    #[test]
+    fn should_generate_seed_only_for_its_own_size() {
+        let domino = [Vector2::new(0, 0), Vector2::new(1, 0)];
+        assert_eq!(generate_extensions(&domino, 2).len(), 1);
+        assert!(generate_extensions(&domino, 1).is_empty());
+    }
+
+    #[test]
+    fn should_generate_exactly_the_same_as_up_to_size() {
+        let known_polys = generate_shapes_up_to_size::<ShapeWithGrid, i32>(7);
+        for n in 1..=7 {
+            assert_eq!(generate_shapes_exactly::<ShapeWithGrid, i32>(n), known_polys[&n], "n={}", n);
+        }
+        assert!(generate_shapes_exactly::<ShapeWithGrid, i32>(0).is_empty());
+    }
+
+    #[test]
+    fn should_count_the_same_without_keeping_shapes() {
+        let with_grid = generate_shapes_up_to_size::<ShapeWithGrid, i32>(8);
+        let counts = count_shapes_up_to_size(8).iter().map(|s| s.found).collect::<Vec<_>>();
+        assert_eq!(counts, (1..=8).map(|n| with_grid[&n].len()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn should_count_the_same_with_both_algorithms() {
+        let with_grid = generate_shapes_up_to_size::<ShapeWithGrid, i32>(8);
+        let minimal = generate_shapes_up_to_size::<ShapeMinimal, i8>(8);
+        for n in 1..=8 {
+            assert_eq!(with_grid[&n].len(), minimal[&n].len(), "n={}", n);
+        }
+    }
+
+    #[test]
+    fn should_write_polys_grouped_by_size() {
+        let polys = generate_shapes_up_to_size::<ShapeWithGrid, i32>(3);
+        let mut out = Vec::new();
+        write_polys(&mut out, 3, Format::Ascii, DisplayStyle::default(), None, &polys).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("Polys with size n=1\nO\n\nPolys with size n=2\n"));
+        assert!(out.contains("Polys with size n=3\n"));
+    }
+
+    #[test]
+    fn should_write_polys_in_the_same_order_every_run() {
+        let write = || {
+            let polys = generate_shapes_up_to_size::<ShapeWithGrid, i32>(6);
+            let mut out = Vec::new();
+            write_polys(&mut out, 6, Format::Ascii, DisplayStyle::default(), None, &polys).unwrap();
+            out
+        };
+        assert_eq!(write(), write());
+    }
+
+    #[test]
+    fn should_stop_generating_once_over_memory_budget() {
+        let polys = generate_shapes_up_to_size::<ShapeWithGrid, i32>(3);
+        let budget = polys.values().flatten().map(|poly| poly.estimated_bytes()).sum();
+
+        let (budgeted, stats) = generate_budgeted_shapes_up_to_size(6, budget, &|_| true);
+        assert_eq!(stats.len(), 3);
+        assert_eq!(budgeted.keys().max(), Some(&3));
+        assert_eq!(budgeted[&3], polys[&3]);
+
+        let (budgeted, stats) = generate_budgeted_shapes_up_to_size(6, 0, &|_| true);
+        assert!(stats.is_empty());
+        assert!(budgeted.is_empty());
+    }
+
+    #[test]
+    fn should_stop_growing_a_size_once_over_memory_budget() {
+        let known = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(8);
+        let known_bytes: usize = known.values().flatten().map(|poly| poly.estimated_bytes()).sum();
+        let options = GrowOptions {
+            budget: Some((known_bytes + 10 * size_of::<ShapeWithGrid>(), ShapeWithGrid::estimated_bytes)),
+            parallel_threshold: usize::MAX,
+            ..Default::default()
+        };
+        let (budgeted, stats) = grow_shapes_with_size(9, &known, &|_| true, options);
+        let (_, all_stats) = generate_shapes_with_size(9, &known);
+        // stopped within the first few parents, rather than after growing all of size 9
+        assert!(budgeted.len() < 20, "{}", budgeted.len());
+        assert!(stats.polys_tried < all_stats.polys_tried / 10, "{} vs {}", stats.polys_tried, all_stats.polys_tried);
+    }
+
+    #[test]
+    fn should_not_overflow_huge_memory_budget() {
+        let config = GenerationConfig { max_n: 4, max_memory_mb: Some(usize::MAX), ..Default::default() };
+        assert_eq!(generate_polys(config).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn should_self_check_counts_by_burnside() {
+        let mut polys = generate_shapes_up_to_size::<ShapeWithGrid, i32>(6);
+        assert!(self_check(6, 6, &polys).is_ok());
+
+        // one poly missing isn't a rotated duplicate, but it's one fewer than the fixed polys add up to
+        let missing = polys.get_mut(&6).unwrap();
+        let some_poly = missing.iter().next().unwrap().fingerprint();
+        missing.retain(|poly| poly.fingerprint() != some_poly);
+        assert!(matches!(self_check(6, 6, &polys), Err(PolyError::Burnside(6, 60, 59))));
+        // unless that size isn't complete anyway
+        assert!(self_check(6, 5, &polys).is_ok());
+    }
+
+    #[test]
+    fn should_write_canonical_grids_per_size() {
+        let polys = generate_shapes_up_to_size::<ShapeWithGrid, i32>(3);
+        let mut out = Vec::new();
+        write_canonical(&mut out, 3, &polys).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "Canonical grids with size n=1");
+        assert_eq!(lines[1], "1x1 rotation=0 grid=0000000000000001");
+        assert_eq!(lines[2], "Canonical grids with size n=2");
+        assert_eq!(lines.len(), 7);
+    }
+
+    #[test]
+    fn should_limit_written_polys_per_size() {
+        let polys = generate_shapes_up_to_size::<ShapeWithGrid, i32>(4);
+        let mut out = Vec::new();
+        write_polys(&mut out, 4, Format::Compact, DisplayStyle::default(), Some(2), &polys).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        let n4: Vec<&str> = out.split("Polys with size n=4\n").nth(1).unwrap().lines().collect();
+        assert_eq!(n4.len(), 3);
+        assert_eq!(n4[2], "... and 5 more");
+        // sizes with fewer polys than the limit are written in full
+        assert!(!out.split("Polys with size n=4").next().unwrap().contains("more"));
+    }
+
+    #[test]
+    fn should_fail_to_write_polys_to_unwritable_path() {
+        let polys = generate_shapes_up_to_size::<ShapeWithGrid, i32>(1);
+        let result = write_polys_to_file(Path::new("/nonexistent/polys.txt"), 1, Format::Ascii, DisplayStyle::default(), &polys);
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,86 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::io;
+use std::path::PathBuf;
+
+use crate::poly_2d::shape::shape_error::ParseError;
+
+#[derive(Debug)]
+pub enum PolyError {
+    // only the square lattice has corner neighbors
+    KingOnHex,
+    // tori are only made of squares sharing an edge
+    TorusOnlySquare,
+    Write(PathBuf, io::Error),
+    Read(PathBuf, io::Error),
+    // with the 1-based line number
+    Parse(PathBuf, usize, ParseError),
+    // the width and height of a box with more cells than a mask has bits
+    BoxTooLarge(usize, usize),
+    // the requested number of dimensions, which has no shapes yet
+    UnsupportedDimensions(u8),
+    // the size at which --self-check found rotated duplicates
+    SelfCheck(usize),
+    // the sizes at which --compare-algorithms found different counts, with the count of A32 and then B8
+    AlgorithmsDisagree(Vec<(usize, usize, usize)>),
+    // the size, and how many one-sided polys Burnside's lemma expects and how many were found by canonicalizing
+    Burnside(usize, usize, usize),
+    // a flag that only A32 generating polyominoes acts on, given for any other kind of run
+    OnlyA32(&'static str),
+    // a flag that's given for polycubes in 3 dimensions, but only applies to polys in 2
+    Only2d(&'static str),
+}
+
+impl Display for PolyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolyError::KingOnHex => write!(f, "king connectivity is only supported on the square lattice"),
+            PolyError::TorusOnlySquare => write!(f, "tori are only supported on the square lattice with edge connectivity"),
+            PolyError::Write(path, e) => write!(f, "could not write to {}: {}", path.display(), e),
+            PolyError::Read(path, e) => write!(f, "could not read {}: {}", path.display(), e),
+            PolyError::Parse(path, line, e) => write!(f, "could not parse {} line {}: {}", path.display(), line, e),
+            PolyError::BoxTooLarge(width, height) => {
+                write!(f, "a {}x{} box has more than 64 cells, which isn't supported", width, height)
+            }
+            PolyError::UnsupportedDimensions(d) => write!(f, "{} dimensional polycubes are not implemented yet", d),
+            PolyError::SelfCheck(n) => write!(f, "self check failed, some polys of size {} are rotations of each other", n),
+            PolyError::Burnside(n, expected, found) => write!(
+                f,
+                "Burnside's lemma gives {} one-sided polys of size {}, but canonicalizing found {}",
+                expected, n, found
+            ),
+            PolyError::OnlyA32(flag) => write!(
+                f,
+                "{} is only supported by algorithm A32 on the square lattice with edge connectivity, without --torus or \
+                 --count-only",
+                flag
+            ),
+            PolyError::Only2d(flag) => write!(f, "{} is only supported in 2 dimensions", flag),
+            PolyError::AlgorithmsDisagree(disagreements) => {
+                write!(f, "A32 and B8 found different numbers of polys:")?;
+                for (n, a32, b8) in disagreements {
+                    write!(f, "\n  n={}: A32 {} vs B8 {}", n, a32, b8)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for PolyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PolyError::KingOnHex
+            | PolyError::TorusOnlySquare
+            | PolyError::BoxTooLarge(_, _)
+            | PolyError::UnsupportedDimensions(_)
+            | PolyError::SelfCheck(_)
+            | PolyError::AlgorithmsDisagree(_)
+            | PolyError::Burnside(_, _, _)
+            | PolyError::OnlyA32(_)
+            | PolyError::Only2d(_) => None,
+            PolyError::Write(_, e) | PolyError::Read(_, e) => Some(e),
+            PolyError::Parse(_, _, e) => Some(e),
+        }
+    }
+}
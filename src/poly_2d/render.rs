@@ -0,0 +1,163 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Cursor};
+use std::path::Path;
+
+use image::{ImageFormat, Rgba, RgbaImage};
+
+use crate::poly_2d::shape::shape_with_grid::ShapeWithGrid;
+
+const FILLED: Rgba<u8> = Rgba([0, 0, 0, 255]);
+const EMPTY: Rgba<u8> = Rgba([0, 0, 0, 0]);
+
+impl ShapeWithGrid {
+    // occupied cells as black squares of cell_px pixels, on a transparent background. y_up draws the last row at the
+    // top, like DisplayStyle::y_up
+    pub fn render_png(&self, cell_px: u32, y_up: bool) -> Vec<u8> {
+        encode_png(&self.render(cell_px, y_up))
+    }
+
+    fn render(&self, cell_px: u32, y_up: bool) -> RgbaImage {
+        let cell_px = cell_px.max(1);
+        let width = self.width() as u32 * cell_px;
+        let height = self.height() as u32 * cell_px;
+        RgbaImage::from_fn(width, height, |x, y| {
+            let i_y = (y / cell_px) as usize;
+            let row = self.grid[if y_up { self.grid.len() - 1 - i_y } else { i_y }];
+            if (row >> (x / cell_px)) & 0x1 != 0 { FILLED } else { EMPTY }
+        })
+    }
+}
+
+// all shapes in one image, row by row with the given number of columns. every shape is centered in a tile as big as
+// the largest width and height among them
+pub fn render_gallery(shapes: &[ShapeWithGrid], columns: usize, cell_px: u32, y_up: bool) -> Vec<u8> {
+    encode_png(&gallery(&shapes.iter().collect::<Vec<_>>(), columns, cell_px, y_up))
+}
+
+fn gallery(shapes: &[&ShapeWithGrid], columns: usize, cell_px: u32, y_up: bool) -> RgbaImage {
+    let cell_px = cell_px.max(1);
+    let columns = columns.max(1);
+    let rows = shapes.len().div_ceil(columns).max(1);
+    let tile_width = shapes.iter().map(|s| s.width()).max().unwrap_or(1) as u32 * cell_px;
+    let tile_height = shapes.iter().map(|s| s.height()).max().unwrap_or(1) as u32 * cell_px;
+
+    let mut gallery = RgbaImage::from_pixel(columns as u32 * tile_width, rows as u32 * tile_height, EMPTY);
+    for (i, shape) in shapes.iter().enumerate() {
+        let tile = shape.render(cell_px, y_up);
+        let x = (i % columns) as u32 * tile_width + (tile_width - tile.width()) / 2;
+        let y = (i / columns) as u32 * tile_height + (tile_height - tile.height()) / 2;
+        image::imageops::replace(&mut gallery, &tile, x as i64, y as i64);
+    }
+    gallery
+}
+
+fn encode_png(image: &RgbaImage) -> Vec<u8> {
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, ImageFormat::Png)
+        .expect("encoding to memory can't fail");
+    png.into_inner()
+}
+
+// all polys of one size in a roughly square gallery, in sorted order so the same set always gives the same image
+pub fn write_gallery(path: &Path, polys: &HashSet<ShapeWithGrid>, cell_px: u32, y_up: bool) -> io::Result<()> {
+    let mut sorted: Vec<&ShapeWithGrid> = polys.iter().collect();
+    sorted.sort();
+    let columns = (sorted.len() as f64).sqrt().ceil() as usize;
+    fs::write(path, encode_png(&gallery(&sorted, columns, cell_px, y_up)))
+}
+
+// one file per poly, named after the poly's size and its position in the set
+pub fn write_pngs(dir: &Path, n: usize, polys: &HashSet<ShapeWithGrid>, cell_px: u32, y_up: bool) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    for (i, poly) in polys.iter().enumerate() {
+        fs::write(dir.join(format!("{}-{}.png", n, i)), poly.render_png(cell_px, y_up))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::Vector2;
+
+    use crate::poly_2d::render::render_gallery;
+    use crate::poly_2d::shape::shape_generic::ShapeN;
+    use crate::poly_2d::shape::shape_with_grid::ShapeWithGrid;
+
+    fn decode(png: &[u8]) -> image::RgbaImage {
+        image::load_from_memory_with_format(png, image::ImageFormat::Png).unwrap().to_rgba8()
+    }
+
+    #[test]
+    fn should_render_png_sized_to_bounds() {
+        let l = ShapeWithGrid::new(vec![
+            Vector2::new(0, 0),
+            Vector2::new(0, 1),
+            Vector2::new(0, 2),
+            Vector2::new(1, 0),
+        ]);
+        let image = decode(&l.render_png(10, false));
+        assert_eq!(image.dimensions(), (l.width() as u32 * 10, l.height() as u32 * 10));
+    }
+
+    #[test]
+    fn should_render_png_of_1_wide_and_1_tall_shapes() {
+        let cell = ShapeWithGrid::new(vec![Vector2::new(0, 0)]);
+        assert_eq!(decode(&cell.render_png(4, false)).dimensions(), (4, 4));
+
+        let bar = ShapeWithGrid::new((0..5).map(|x| Vector2::new(x, 0)).collect());
+        let image = decode(&bar.render_png(4, false));
+        assert_eq!(image.dimensions(), (bar.width() as u32 * 4, bar.height() as u32 * 4));
+        assert!(image.pixels().all(|p| p.0[3] == 255));
+    }
+
+    #[test]
+    fn should_size_gallery_to_columns_and_largest_tile() {
+        let cell = ShapeWithGrid::new(vec![Vector2::new(0, 0)]);
+        let bar = ShapeWithGrid::new((0..3).map(|x| Vector2::new(x, 0)).collect());
+        let l = ShapeWithGrid::new(vec![Vector2::new(0, 0), Vector2::new(0, 1), Vector2::new(1, 0)]);
+        let tile = (
+            [&cell, &bar, &l].iter().map(|s| s.width()).max().unwrap() as u32 * 4,
+            [&cell, &bar, &l].iter().map(|s| s.height()).max().unwrap() as u32 * 4,
+        );
+        // the bar is 3 long, in whichever orientation is canonical
+        assert_eq!(tile.0.max(tile.1), 3 * 4);
+
+        // 3 shapes in 2 columns take 2 rows
+        let shapes = [cell, bar, l];
+        let image = decode(&render_gallery(&shapes, 2, 4, false));
+        assert_eq!(image.dimensions(), (2 * tile.0, 2 * tile.1));
+
+        // a single row is as wide as all columns, even when some are empty
+        let image = decode(&render_gallery(&shapes, 5, 4, false));
+        assert_eq!(image.dimensions(), (5 * tile.0, tile.1));
+    }
+
+    #[test]
+    fn should_center_shapes_in_gallery_tiles() {
+        let cell = ShapeWithGrid::new(vec![Vector2::new(0, 0)]);
+        let bar = ShapeWithGrid::new((0..3).map(|x| Vector2::new(x, 0)).collect());
+        let (width, height) = (bar.width() as u32, bar.height() as u32);
+        let image = decode(&render_gallery(&[cell, bar], 2, 1, false));
+        // the tiles are as big as the bar, and the cell sits in the middle of the first
+        assert_eq!(image.dimensions(), (2 * width, height));
+        let filled: Vec<(u32, u32)> =
+            image.enumerate_pixels().filter(|(x, _, p)| *x < width && p.0[3] != 0).map(|(x, y, _)| (x, y)).collect();
+        assert_eq!(filled, vec![(width / 2, height / 2)]);
+        assert_eq!(image.pixels().filter(|p| p.0[3] != 0).count(), 4);
+    }
+
+    #[test]
+    fn should_render_png_mirrored_vertically_with_y_up() {
+        let l = ShapeWithGrid::new(vec![Vector2::new(0, 0), Vector2::new(0, 1), Vector2::new(0, 2), Vector2::new(1, 0)]);
+        let (down, up) = (decode(&l.render_png(1, false)), decode(&l.render_png(1, true)));
+        assert_ne!(down, up);
+        assert_eq!(image::imageops::flip_vertical(&down), up);
+    }
+
+    #[test]
+    fn should_render_empty_gallery() {
+        assert_eq!(decode(&render_gallery(&[], 3, 4, false)).dimensions(), (3 * 4, 4));
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, Write};
+
+use crate::poly_2d::poly::continue_generation;
+use crate::poly_2d::shape::shape_with_grid::ShapeWithGrid;
+use crate::poly_2d::symmetry::to_free;
+
+// answers one size per line of input with the number of free, one-sided and fixed polys of that size, until the
+// input ends. all sizes generated so far are kept, so sizes up to the largest asked for are answered without
+// generating anything
+pub fn run_repl(input: impl BufRead, out: &mut impl Write) -> io::Result<()> {
+    let mut counter = Counter::default();
+    for line in input.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match line.parse::<usize>() {
+            Ok(n) if n > 0 => {
+                let (counts, cached) = counter.count(n);
+                writeln!(
+                    out,
+                    "n={}: free {} one-sided {} fixed {}{}",
+                    n,
+                    counts.free,
+                    counts.one_sided,
+                    counts.fixed,
+                    if cached { " (cached)" } else { "" }
+                )?;
+            }
+            _ => writeln!(out, "error: not a size of at least 1: {}", line)?,
+        }
+        // so that whoever is waiting for the answer gets it now
+        out.flush()?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Counts {
+    free: usize,
+    one_sided: usize,
+    fixed: usize,
+}
+
+#[derive(Default)]
+struct Counter {
+    known_polys: HashMap<usize, HashSet<ShapeWithGrid>>,
+}
+
+impl Counter {
+    // the counts for size n, and whether that size had been generated before
+    fn count(&mut self, n: usize) -> (Counts, bool) {
+        let cached = self.known_polys.contains_key(&n);
+        if !cached {
+            self.known_polys = continue_generation(std::mem::take(&mut self.known_polys), n);
+        }
+        let polys = &self.known_polys[&n];
+        let counts = Counts {
+            free: to_free(polys).len(),
+            one_sided: polys.len(),
+            fixed: polys.iter().map(|poly| poly.rotation_multiplicity()).sum(),
+        };
+        (counts, cached)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::poly_2d::repl::run_repl;
+
+    #[test]
+    fn should_answer_each_size_and_reuse_generated_sizes() {
+        let mut out = Vec::new();
+        run_repl("3\n5\n3\n".as_bytes(), &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "n=3: free 2 one-sided 2 fixed 6\n\
+             n=5: free 12 one-sided 18 fixed 63\n\
+             n=3: free 2 one-sided 2 fixed 6 (cached)\n"
+        );
+    }
+
+    #[test]
+    fn should_report_invalid_input_and_carry_on() {
+        let mut out = Vec::new();
+        run_repl("four\n\n0\n4".as_bytes(), &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "error: not a size of at least 1: four\n\
+             error: not a size of at least 1: 0\n\
+             n=4: free 5 one-sided 7 fixed 19\n"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use nalgebra::{Matrix2, Rotation2};
+use nalgebra::{Matrix2, Matrix3, Rotation2, Rotation3};
 
 pub static ROTATIONS32: &[Rotation2<i32>] = &[
     Rotation2::from_matrix_unchecked(Matrix2::new(1, 0, 0, 1)), // 0 deg ccw
@@ -7,9 +7,118 @@ pub static ROTATIONS32: &[Rotation2<i32>] = &[
     Rotation2::from_matrix_unchecked(Matrix2::new(0, 1, -1, 0)), // 270 deg ccw
 ];
 
+// not rotations, but stored as such so they can be applied the same way.
+// together with ROTATIONS32 they form the dihedral group D4 (all symmetries of the square)
+pub static REFLECTIONS32: &[Rotation2<i32>] = &[
+    Rotation2::from_matrix_unchecked(Matrix2::new(-1, 0, 0, 1)), // across y axis
+    Rotation2::from_matrix_unchecked(Matrix2::new(1, 0, 0, -1)), // across x axis
+    Rotation2::from_matrix_unchecked(Matrix2::new(0, 1, 1, 0)), // across y = x
+    Rotation2::from_matrix_unchecked(Matrix2::new(0, -1, -1, 0)), // across y = -x
+];
+
 pub static ROTATIONS8: &[Rotation2<i8>] = &[
     Rotation2::from_matrix_unchecked(Matrix2::new(1, 0, 0, 1)), // 0 deg ccw
     Rotation2::from_matrix_unchecked(Matrix2::new(0, -1, 1, 0)), // 90 deg ccw
     Rotation2::from_matrix_unchecked(Matrix2::new(-1, 0, 0, -1)), // 180 deg ccw
     Rotation2::from_matrix_unchecked(Matrix2::new(0, 1, -1, 0)), // 270 deg ccw
-];
\ No newline at end of file
+];
+
+// in axial coordinates (see MOVES_HEX)
+pub static ROTATIONS_HEX: &[Rotation2<i32>] = &[
+    Rotation2::from_matrix_unchecked(Matrix2::new(1, 0, 0, 1)), // 0 deg ccw
+    Rotation2::from_matrix_unchecked(Matrix2::new(0, -1, 1, 1)), // 60 deg ccw
+    Rotation2::from_matrix_unchecked(Matrix2::new(-1, -1, 1, 0)), // 120 deg ccw
+    Rotation2::from_matrix_unchecked(Matrix2::new(-1, 0, 0, -1)), // 180 deg ccw
+    Rotation2::from_matrix_unchecked(Matrix2::new(0, 1, -1, -1)), // 240 deg ccw
+    Rotation2::from_matrix_unchecked(Matrix2::new(1, 1, -1, 0)), // 300 deg ccw
+];
+
+// the 24 proper rotations of the cube: identity, quarter turns about the axes, half turns about the edge diagonals
+// and third turns about the body diagonals
+pub static ROTATIONS24: &[Rotation3<i32>] = &[
+    Rotation3::from_matrix_unchecked(Matrix3::new(1, 0, 0, 0, 1, 0, 0, 0, 1)), // identity
+    Rotation3::from_matrix_unchecked(Matrix3::new(1, 0, 0, 0, 0, -1, 0, 1, 0)), // 90 deg ccw about x
+    Rotation3::from_matrix_unchecked(Matrix3::new(1, 0, 0, 0, -1, 0, 0, 0, -1)), // 180 deg ccw about x
+    Rotation3::from_matrix_unchecked(Matrix3::new(1, 0, 0, 0, 0, 1, 0, -1, 0)), // 270 deg ccw about x
+    Rotation3::from_matrix_unchecked(Matrix3::new(0, 0, 1, 0, 1, 0, -1, 0, 0)), // 90 deg ccw about y
+    Rotation3::from_matrix_unchecked(Matrix3::new(-1, 0, 0, 0, 1, 0, 0, 0, -1)), // 180 deg ccw about y
+    Rotation3::from_matrix_unchecked(Matrix3::new(0, 0, -1, 0, 1, 0, 1, 0, 0)), // 270 deg ccw about y
+    Rotation3::from_matrix_unchecked(Matrix3::new(0, -1, 0, 1, 0, 0, 0, 0, 1)), // 90 deg ccw about z
+    Rotation3::from_matrix_unchecked(Matrix3::new(-1, 0, 0, 0, -1, 0, 0, 0, 1)), // 180 deg ccw about z
+    Rotation3::from_matrix_unchecked(Matrix3::new(0, 1, 0, -1, 0, 0, 0, 0, 1)), // 270 deg ccw about z
+    Rotation3::from_matrix_unchecked(Matrix3::new(0, 1, 0, 1, 0, 0, 0, 0, -1)), // 180 deg about x = y, z = 0
+    Rotation3::from_matrix_unchecked(Matrix3::new(0, -1, 0, -1, 0, 0, 0, 0, -1)), // 180 deg about x = -y, z = 0
+    Rotation3::from_matrix_unchecked(Matrix3::new(0, 0, 1, 0, -1, 0, 1, 0, 0)), // 180 deg about x = z, y = 0
+    Rotation3::from_matrix_unchecked(Matrix3::new(0, 0, -1, 0, -1, 0, -1, 0, 0)), // 180 deg about x = -z, y = 0
+    Rotation3::from_matrix_unchecked(Matrix3::new(-1, 0, 0, 0, 0, 1, 0, 1, 0)), // 180 deg about y = z, x = 0
+    Rotation3::from_matrix_unchecked(Matrix3::new(-1, 0, 0, 0, 0, -1, 0, -1, 0)), // 180 deg about y = -z, x = 0
+    Rotation3::from_matrix_unchecked(Matrix3::new(0, 0, 1, 1, 0, 0, 0, 1, 0)), // 120 deg ccw about (1, 1, 1)
+    Rotation3::from_matrix_unchecked(Matrix3::new(0, 1, 0, 0, 0, 1, 1, 0, 0)), // 240 deg ccw about (1, 1, 1)
+    Rotation3::from_matrix_unchecked(Matrix3::new(0, -1, 0, 0, 0, 1, -1, 0, 0)), // 120 deg ccw about (-1, 1, 1)
+    Rotation3::from_matrix_unchecked(Matrix3::new(0, 0, -1, -1, 0, 0, 0, 1, 0)), // 240 deg ccw about (-1, 1, 1)
+    Rotation3::from_matrix_unchecked(Matrix3::new(0, -1, 0, 0, 0, -1, 1, 0, 0)), // 120 deg ccw about (1, -1, 1)
+    Rotation3::from_matrix_unchecked(Matrix3::new(0, 0, 1, -1, 0, 0, 0, -1, 0)), // 240 deg ccw about (1, -1, 1)
+    Rotation3::from_matrix_unchecked(Matrix3::new(0, 1, 0, 0, 0, -1, -1, 0, 0)), // 120 deg ccw about (1, 1, -1)
+    Rotation3::from_matrix_unchecked(Matrix3::new(0, 0, -1, 1, 0, 0, 0, -1, 0)), // 240 deg ccw about (1, 1, -1)
+];
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+    use std::fmt::Debug;
+    use std::hash::Hash;
+
+    use nalgebra::{Matrix2, Matrix3};
+
+    use crate::poly_2d::rotation::{REFLECTIONS32, ROTATIONS24, ROTATIONS32, ROTATIONS8, ROTATIONS_HEX};
+
+    // distinct elements, the first of which is the identity, closed under multiplication and with every inverse
+    fn assert_group<M: Copy + Eq + Hash + Debug>(name: &str, elements: &[M], identity: M, mul: impl Fn(M, M) -> M) {
+        let set: HashSet<M> = elements.iter().copied().collect();
+        assert_eq!(set.len(), elements.len(), "{} has duplicates", name);
+        assert_eq!(elements[0], identity, "{} doesn't start with the identity", name);
+        for &a in elements {
+            assert!(elements.iter().any(|&b| mul(a, b) == identity), "{} has no inverse of {:?}", name, a);
+            for &b in elements {
+                assert!(set.contains(&mul(a, b)), "{} doesn't contain {:?} * {:?}", name, a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn should_form_groups() {
+        let mul2 = |a: Matrix2<i32>, b: Matrix2<i32>| a * b;
+        let matrices2 = |rotations: &[nalgebra::Rotation2<i32>]| rotations.iter().map(|r| *r.matrix()).collect::<Vec<_>>();
+        assert_group("ROTATIONS32", &matrices2(ROTATIONS32), Matrix2::identity(), mul2);
+        assert_group("ROTATIONS_HEX", &matrices2(ROTATIONS_HEX), Matrix2::identity(), mul2);
+        let d4: Vec<Matrix2<i32>> = matrices2(ROTATIONS32).into_iter().chain(matrices2(REFLECTIONS32)).collect();
+        assert_group("ROTATIONS32 and REFLECTIONS32", &d4, Matrix2::identity(), mul2);
+
+        let rotations8: Vec<Matrix2<i8>> = ROTATIONS8.iter().map(|r| *r.matrix()).collect();
+        assert_group("ROTATIONS8", &rotations8, Matrix2::identity(), |a, b| a * b);
+        // the same rotations in the same order, so both representations canonicalize alike
+        assert_eq!(rotations8.iter().map(|m| m.map(|x| x as i32)).collect::<Vec<_>>(), matrices2(ROTATIONS32));
+
+        let rotations24: Vec<Matrix3<i32>> = ROTATIONS24.iter().map(|r| *r.matrix()).collect();
+        assert_group("ROTATIONS24", &rotations24, Matrix3::identity(), |a, b| a * b);
+    }
+
+    #[test]
+    fn should_catch_malformed_tables() {
+        let mul = |a: Matrix2<i32>, b: Matrix2<i32>| a * b;
+        // 90 deg ccw twice, so 270 deg is missing
+        let typo = [Matrix2::identity(), Matrix2::new(0, -1, 1, 0), Matrix2::new(-1, 0, 0, -1), Matrix2::new(0, -1, 1, 0)];
+        assert!(std::panic::catch_unwind(|| assert_group("typo", &typo, Matrix2::identity(), mul)).is_err());
+        // a reflection without the rest of D4
+        let partial = [Matrix2::identity(), Matrix2::new(0, -1, 1, 0), Matrix2::new(-1, 0, 0, 1)];
+        assert!(std::panic::catch_unwind(|| assert_group("partial", &partial, Matrix2::identity(), mul)).is_err());
+    }
+
+    #[test]
+    fn should_only_have_proper_rotations() {
+        for r in ROTATIONS24 {
+            assert_eq!(r.matrix().map(|x| x as f64).determinant(), 1.0, "{}", r.matrix());
+            assert_eq!(r.matrix() * r.matrix().transpose(), Matrix3::identity(), "{}", r.matrix());
+        }
+    }
+}
@@ -1,8 +1,8 @@
-use std::cmp;
+use std::cmp::{self, Ordering};
 
 use nalgebra::{Rotation2, Vector2};
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct BoundingBoxTwoPoints {
     pub p0: Vector2<i32>,
     pub p1: Vector2<i32>,
@@ -11,7 +11,7 @@ pub struct BoundingBoxTwoPoints {
 
 impl BoundingBoxTwoPoints {
     pub fn from(points: &[Vector2<i32>]) -> BoundingBoxTwoPoints {
-        return BoundingBoxTwoPoints {
+        BoundingBoxTwoPoints {
             p0: Vector2::new(
                 points.iter().map(|p| p.x).min().unwrap(),
                 points.iter().map(|p| p.y).min().unwrap(),
@@ -20,17 +20,17 @@ impl BoundingBoxTwoPoints {
                 points.iter().map(|p| p.x).max().unwrap(),
                 points.iter().map(|p| p.y).max().unwrap(),
             ),
-        };
+        }
     }
 
-    pub fn min(&self) -> Vector2<i32> {
+    pub fn min_corner(&self) -> Vector2<i32> {
         Vector2::new(
             cmp::min(self.p0.x, self.p1.x),
             cmp::min(self.p0.y, self.p1.y),
         )
     }
 
-    pub fn max(&self) -> Vector2<i32> {
+    pub fn max_corner(&self) -> Vector2<i32> {
         Vector2::new(
             cmp::max(self.p0.x, self.p1.x),
             cmp::max(self.p0.y, self.p1.y),
@@ -38,6 +38,20 @@ impl BoundingBoxTwoPoints {
     }
 }
 
+// lexicographic by corners, so that ties between equal grids can be broken deterministically. nalgebra only orders
+// vectors partially, by component
+impl Ord for BoundingBoxTwoPoints {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.p0.x, self.p0.y, self.p1.x, self.p1.y).cmp(&(other.p0.x, other.p0.y, other.p1.x, other.p1.y))
+    }
+}
+
+impl PartialOrd for BoundingBoxTwoPoints {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl std::ops::Mul<&BoundingBoxTwoPoints> for &Rotation2<i32> {
     type Output = BoundingBoxTwoPoints;
 
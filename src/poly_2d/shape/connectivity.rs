@@ -0,0 +1,58 @@
+use std::collections::HashSet;
+
+use nalgebra::Vector2;
+
+use crate::poly_2d::moves::MOVES32;
+
+// whether all points are reachable from each other via their 4 neighbors, i.e. form a valid polyomino
+pub fn is_connected(points: &[Vector2<i32>]) -> bool {
+    let Some(&first) = points.first() else {
+        return false;
+    };
+
+    let remaining: HashSet<Vector2<i32>> = points.iter().copied().collect();
+    let mut visited: HashSet<Vector2<i32>> = HashSet::from([first]);
+    let mut stack = vec![first];
+    while let Some(p) = stack.pop() {
+        for m in MOVES32 {
+            let neighbor = p + m;
+            if remaining.contains(&neighbor) && visited.insert(neighbor) {
+                stack.push(neighbor);
+            }
+        }
+    }
+
+    visited.len() == remaining.len()
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::Vector2;
+
+    use crate::poly_2d::shape::connectivity::is_connected;
+
+    #[test]
+    fn should_accept_connected_l() {
+        assert!(is_connected(&[
+            Vector2::new(0, 0),
+            Vector2::new(0, 1),
+            Vector2::new(0, 2),
+            Vector2::new(1, 0),
+        ]));
+    }
+
+    #[test]
+    fn should_reject_disconnected_pair() {
+        assert!(!is_connected(&[Vector2::new(0, 0), Vector2::new(1, 1)]));
+    }
+
+    #[test]
+    fn should_accept_single_cell() {
+        assert!(is_connected(&[Vector2::new(3, -2)]));
+    }
+
+    #[test]
+    fn should_reject_empty() {
+        assert!(!is_connected(&[]));
+    }
+}
@@ -0,0 +1,102 @@
+use std::fmt::Formatter;
+use std::sync::Once;
+
+// each row of a grid is a single u64, so shapes can't be wider than this in any rotation
+// TODO lift this limit by allowing multiple words per row
+pub const MAX_GRID_WIDTH: u32 = u64::BITS;
+
+static REPORT_TOO_WIDE: Once = Once::new();
+
+// the bit for column x within a grid row
+pub fn column_bit(x: i32) -> u64 {
+    debug_assert!(
+        (0..MAX_GRID_WIDTH as i32).contains(&x),
+        "column {} is outside of the {} columns a grid row can hold",
+        x,
+        MAX_GRID_WIDTH
+    );
+    match u32::try_from(x).ok().and_then(|x| 0x1u64.checked_shl(x)) {
+        Some(bit) => bit,
+        None => {
+            REPORT_TOO_WIDE.call_once(|| {
+                eprintln!(
+                    "error: shapes wider than {} cells can't be represented, results will be wrong",
+                    MAX_GRID_WIDTH
+                )
+            });
+            0
+        }
+    }
+}
+
+// the number of columns up to and including the rightmost occupied cell of any row
+pub fn populated_width(grid: &[u64]) -> usize {
+    grid.iter().map(|row| (u64::BITS - row.leading_zeros()) as usize).max().unwrap_or(0)
+}
+
+// the characters grids are drawn with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayStyle {
+    pub filled: char,
+    pub empty: char,
+    // draw the last row first, so that y grows upwards as on a cartesian plane
+    pub y_up: bool,
+}
+
+impl Default for DisplayStyle {
+    fn default() -> Self {
+        DisplayStyle { filled: 'O', empty: ' ', y_up: false }
+    }
+}
+
+// one line per row, with one character per cell
+pub fn write_rows(f: &mut Formatter, grid: &[u64], width: usize, style: DisplayStyle) -> std::fmt::Result {
+    for i_y in 0..grid.len() {
+        let row = grid[if style.y_up { grid.len() - 1 - i_y } else { i_y }];
+        for i_x in 0..width {
+            let present = (row >> i_x) & 0x1 != 0;
+            write!(f, "{}", if present { style.filled } else { style.empty })?;
+        }
+        writeln!(f)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::Vector2;
+
+    use crate::poly_2d::shape::grid::{column_bit, populated_width, MAX_GRID_WIDTH};
+    use crate::poly_2d::shape::shape_generic::ShapeN;
+    use crate::poly_2d::shape::shape_with_grid::ShapeWithGrid;
+
+    #[test]
+    fn should_set_column_bits() {
+        assert_eq!(column_bit(0), 0b1);
+        assert_eq!(column_bit(3), 0b1000);
+        assert_eq!(column_bit(MAX_GRID_WIDTH as i32 - 1), 0x1 << 63);
+    }
+
+    #[test]
+    fn should_measure_populated_width() {
+        assert_eq!(populated_width(&[]), 0);
+        assert_eq!(populated_width(&[0b1]), 1);
+        assert_eq!(populated_width(&[0b10, 0b1001, 0b1]), 4);
+        assert_eq!(populated_width(&[0x1 << 63]), 64);
+    }
+
+    #[test]
+    fn should_fit_64_wide_bar() {
+        // canonicalizes to the horizontal rotation, a single full row
+        let bar = ShapeWithGrid::new((0..64).map(|y| Vector2::new(0, y)).collect());
+        assert_eq!(bar.grid, vec![u64::MAX]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "outside of the 64 columns")]
+    fn should_reject_65_wide_bar() {
+        ShapeWithGrid::new((0..65).map(|x| Vector2::new(x, 0)).collect());
+    }
+}
@@ -1,3 +1,10 @@
 pub mod shape_with_grid;
 pub mod bounding_box_two_points;
 pub mod shape_minimal;
+pub mod shape_generic;
+pub mod shape_error;
+pub mod connectivity;
+pub mod shape_on_lattice;
+pub mod grid;
+pub mod weighted_shape;
+pub mod shape_cubes;
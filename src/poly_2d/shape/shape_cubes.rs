@@ -0,0 +1,128 @@
+use std::fmt::Display;
+use std::hash::{Hash, Hasher};
+
+use nalgebra::Vector3;
+
+use crate::poly_2d::moves::MOVES_CUBE;
+use crate::poly_2d::rotation::ROTATIONS24;
+use crate::poly_2d::shape::grid::{column_bit, write_rows, DisplayStyle};
+use crate::poly_2d::shape::shape_generic::ShapeN;
+
+// a polycube. the grid holds one 2d grid per z-layer, canonical under ROTATIONS24
+#[derive(Debug)]
+pub struct ShapeCubes {
+    pub points: Vec<Vector3<i32>>,
+    pub grid: Vec<Vec<u64>>,
+}
+
+impl ShapeN<i32, 3> for ShapeCubes {
+    fn new(points: Vec<Vector3<i32>>) -> ShapeCubes {
+        let grid = canonical_grid(&points);
+        ShapeCubes { points, grid }
+    }
+
+    fn points(&self) -> &Vec<Vector3<i32>> {
+        &self.points
+    }
+
+    fn moves() -> &'static [Vector3<i32>] {
+        MOVES_CUBE
+    }
+}
+
+fn canonical_grid(points: &[Vector3<i32>]) -> Vec<Vec<u64>> {
+    ROTATIONS24
+        .iter()
+        .map(|rotation| {
+            let rotated: Vec<Vector3<i32>> = points.iter().map(|p| rotation * p).collect();
+            let min = Vector3::new(
+                rotated.iter().map(|p| p.x).min().unwrap(),
+                rotated.iter().map(|p| p.y).min().unwrap(),
+                rotated.iter().map(|p| p.z).min().unwrap(),
+            );
+            let max_y = rotated.iter().map(|p| p.y).max().unwrap();
+            let max_z = rotated.iter().map(|p| p.z).max().unwrap();
+
+            let mut grid = vec![vec![0; (max_y - min.y) as usize + 1]; (max_z - min.z) as usize + 1];
+            for p in rotated {
+                let p = p - min;
+                grid[p.z as usize][p.y as usize] |= column_bit(p.x);
+            }
+            grid
+        })
+        .min()
+        .unwrap()
+}
+
+impl PartialEq for ShapeCubes {
+    fn eq(&self, other: &Self) -> bool {
+        self.grid == other.grid
+    }
+}
+
+impl Eq for ShapeCubes {}
+
+impl Hash for ShapeCubes {
+    fn hash<H>(&self, state: &mut H)
+        where
+            H: Hasher,
+    {
+        self.grid.hash(state);
+    }
+}
+
+// each z-layer as a 2d grid, all with the same width and height. empty layers are printed as blank grids, so the
+// headers always count up from 0
+impl Display for ShapeCubes {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let width = self
+            .grid
+            .iter()
+            .flatten()
+            .map(|row| (u64::BITS - row.leading_zeros()) as usize)
+            .max()
+            .unwrap_or(0);
+        for (i_z, layer) in self.grid.iter().enumerate() {
+            writeln!(f, "--- z={} ---", i_z)?;
+            write_rows(f, layer, width, DisplayStyle::default())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use nalgebra::Vector3;
+
+    use crate::poly_2d::rotation::ROTATIONS24;
+    use crate::poly_2d::shape::shape_cubes::ShapeCubes;
+    use crate::poly_2d::shape::shape_generic::ShapeN;
+
+    fn l_tricube() -> Vec<Vector3<i32>> {
+        vec![Vector3::new(0, 0, 0), Vector3::new(1, 0, 0), Vector3::new(0, 0, 1)]
+    }
+
+    #[test]
+    fn should_be_equal_in_any_rotation() {
+        let l = ShapeCubes::new(l_tricube());
+        for rotation in ROTATIONS24 {
+            let rotated = l_tricube().iter().map(|p| rotation * p + Vector3::new(3, -2, 5)).collect();
+            assert_eq!(ShapeCubes::new(rotated), l);
+        }
+    }
+
+    #[test]
+    fn should_print_layers_of_l_tricube() {
+        assert_eq!(ShapeCubes::new(l_tricube()).to_string(), "--- z=0 ---\n \nO\n--- z=1 ---\nO\nO\n");
+    }
+
+    #[test]
+    fn should_print_empty_layers() {
+        let gap = ShapeCubes {
+            points: vec![Vector3::new(0, 0, 0), Vector3::new(1, 1, 2)],
+            grid: vec![vec![0b01, 0b00], vec![0b00, 0b00], vec![0b00, 0b10]],
+        };
+        assert_eq!(gap.to_string(), "--- z=0 ---\nO \n  \n--- z=1 ---\n  \n  \n--- z=2 ---\n  \n O\n");
+    }
+}
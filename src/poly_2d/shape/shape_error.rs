@@ -0,0 +1,58 @@
+use std::error::Error;
+use std::fmt::Display;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ShapeError {
+    Empty,
+    Disconnected,
+    // spans more rows or columns than a grid row has bits
+    TooLarge,
+    // the same point is listed more than once
+    DuplicatePoint,
+}
+
+impl Display for ShapeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            ShapeError::Empty => "shape has no points",
+            ShapeError::Disconnected => "shape is not connected",
+            ShapeError::TooLarge => "shape is more than 64 cells wide or high",
+            ShapeError::DuplicatePoint => "shape has the same point more than once",
+        })
+    }
+}
+
+impl Error for ShapeError {}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    Empty,
+    Disconnected,
+    TooLarge,
+    Malformed,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            ParseError::Empty => "encoded shape has no occupied cells",
+            ParseError::Disconnected => "occupied cells of encoded shape are not connected",
+            ParseError::TooLarge => "encoded shape is more than 64 cells wide or high",
+            ParseError::Malformed => "not a valid encoding of a shape",
+        })
+    }
+}
+
+impl Error for ParseError {}
+
+impl From<ShapeError> for ParseError {
+    fn from(e: ShapeError) -> Self {
+        match e {
+            ShapeError::Empty => ParseError::Empty,
+            ShapeError::Disconnected => ParseError::Disconnected,
+            ShapeError::TooLarge => ParseError::TooLarge,
+            // encodings list each cell once, so this can't come from parsing
+            ShapeError::DuplicatePoint => ParseError::Malformed,
+        }
+    }
+}
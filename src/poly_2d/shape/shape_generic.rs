@@ -0,0 +1,12 @@
+use nalgebra::{SVector, Scalar};
+
+pub trait ShapeN<T: Scalar, const D: usize> {
+    // canonicalizes the given points, so that equal shapes (up to rotation) compare equal
+    fn new(points: Vec<SVector<T, D>>) -> Self;
+
+    fn points(&self) -> &Vec<SVector<T, D>>;
+
+    // the neighbors a shape may grow into from each of its points. returns one of the static tables in moves.rs, so
+    // it costs nothing to call
+    fn moves() -> &'static [SVector<T, D>];
+}
@@ -4,6 +4,11 @@ use std::hash::{Hash, Hasher};
 use itertools::Itertools;
 use nalgebra::{Rotation2, Vector2};
 
+use crate::poly_2d::moves::MOVES8;
+use crate::poly_2d::rotation::ROTATIONS8;
+use crate::poly_2d::shape::grid::column_bit;
+use crate::poly_2d::shape::shape_generic::ShapeN;
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct ShapeMinimal {
     pub points: Vec<Vector2<i8>>,
@@ -11,7 +16,8 @@ pub struct ShapeMinimal {
 }
 
 impl ShapeMinimal {
-    pub fn new(points: Vec<Vector2<i8>>) -> Self {
+    // aligns the points with the origin, but does not canonicalize their rotation
+    pub fn aligned(points: Vec<Vector2<i8>>) -> Self {
         let min: Vector2<i8> = Vector2::new(
             points.iter().map(|p| p.x).min().unwrap(),
             points.iter().map(|p| p.y).min().unwrap(),
@@ -43,10 +49,10 @@ impl ShapeMinimal {
                 let realigned_bounds = rotated_bounds.abs();
 
                 // create 1-hot grid by setting each bit to 1 where there is a point
-                let mut grid = vec![0; realigned_bounds.y as usize + 1];
+                let mut grid: Vec<u64> = vec![0; realigned_bounds.y as usize + 1];
                 for point in &self.points {
                     let point_rotated = rotation * point + realign_offset;
-                    grid[point_rotated.y as usize] |= 0x1 << point_rotated.x
+                    grid[point_rotated.y as usize] |= column_bit(point_rotated.x as i32)
                 }
 
                 (rotation, realign_offset, realigned_bounds, grid)
@@ -54,12 +60,36 @@ impl ShapeMinimal {
             .min_by(|(_, _, _, grid1), (_, _, _, grid2)| grid1.cmp(grid2))
             .unwrap();
 
+        // sort, so that equality and hashing don't depend on the order in which points were added
         let points = self.points.iter()
             .map(|p| rotation * p + realign_offset)
+            .sorted_unstable_by_key(|p| (p.y, p.x))
             .collect_vec();
 
         ShapeMinimal { points, bounds }
     }
+
+    pub fn width(&self) -> usize {
+        self.bounds.x as usize + 1
+    }
+
+    pub fn height(&self) -> usize {
+        self.bounds.y as usize + 1
+    }
+}
+
+impl ShapeN<i8, 2> for ShapeMinimal {
+    fn new(points: Vec<Vector2<i8>>) -> Self {
+        ShapeMinimal::aligned(points).canonical_clone_with_grid(ROTATIONS8)
+    }
+
+    fn points(&self) -> &Vec<Vector2<i8>> {
+        &self.points
+    }
+
+    fn moves() -> &'static [Vector2<i8>] {
+        MOVES8
+    }
 }
 
 impl Hash for ShapeMinimal {
@@ -71,9 +101,17 @@ impl Hash for ShapeMinimal {
 
 #[cfg(test)]
 mod test {
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashSet;
+    use std::hash::{Hash, Hasher};
+
     use nalgebra::Vector2;
+    use rand::rngs::StdRng;
+    use rand::seq::{IteratorRandom, SliceRandom};
+    use rand::SeedableRng;
 
     use crate::poly_2d::rotation::ROTATIONS8;
+    use crate::poly_2d::shape::shape_generic::ShapeN;
     use crate::poly_2d::shape::shape_minimal::ShapeMinimal;
 
     //  xxx
@@ -140,6 +178,20 @@ mod test {
         }
     }
 
+    // rot270, with points sorted by row
+    fn canonical() -> ShapeMinimal {
+        ShapeMinimal {
+            points: vec![
+                Vector2::new(1, 0),
+                Vector2::new(1, 1),
+                Vector2::new(0, 2),
+                Vector2::new(1, 2),
+                Vector2::new(0, 3),
+            ],
+            bounds: Vector2::new(1, 3),
+        }
+    }
+
     #[test]
     fn should_canonicalize() {
         for shape in [
@@ -150,8 +202,95 @@ mod test {
         ] {
             assert_eq!(
                 shape.canonical_clone_with_grid(ROTATIONS8),
-                rot270()
+                canonical()
             );
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn should_measure_canonical_orientation() {
+        // L tetromino, lying down, but canonically standing up
+        // xxx
+        // x
+        let l = ShapeMinimal::new(vec![
+            Vector2::new(0, 0),
+            Vector2::new(1, 0),
+            Vector2::new(2, 0),
+            Vector2::new(0, 1),
+        ]);
+        assert_eq!((l.width(), l.height()), (2, 3));
+        assert!(l.points.iter().all(|p| (p.x as usize) < l.width() && (p.y as usize) < l.height()));
+
+        assert_eq!((canonical().width(), canonical().height()), (2, 4));
+    }
+
+    #[test]
+    fn should_canonicalize_regardless_of_point_order() {
+        let mut reversed = rot0();
+        reversed.points.reverse();
+        assert_eq!(
+            reversed.canonical_clone_with_grid(ROTATIONS8),
+            rot0().canonical_clone_with_grid(ROTATIONS8)
+        );
+    }
+
+    #[test]
+    fn should_return_the_same_static_moves_every_call() {
+        assert!(std::ptr::eq(ShapeMinimal::moves(), ShapeMinimal::moves()));
+    }
+
+    #[test]
+    fn should_move_to_4_unit_neighbors() {
+        let moves: HashSet<Vector2<i8>> = ShapeMinimal::moves().iter().copied().collect();
+        assert_eq!(ShapeMinimal::moves().len(), 4);
+        assert_eq!(
+            moves,
+            HashSet::from([
+                Vector2::new(0, 1),
+                Vector2::new(0, -1),
+                Vector2::new(1, 0),
+                Vector2::new(-1, 0),
+            ])
+        );
+    }
+
+    // grows a polyomino by repeatedly adding a random free neighbor of its cells
+    fn random_polyomino(rng: &mut StdRng, size: usize) -> Vec<Vector2<i8>> {
+        let mut points = vec![Vector2::new(0, 0)];
+        while points.len() < size {
+            let new_point = points.iter()
+                .flat_map(|p| ShapeMinimal::moves().iter().map(move |m| p + m))
+                .filter(|p| !points.contains(p))
+                .choose(rng)
+                .unwrap();
+            points.push(new_point);
+        }
+        points
+    }
+
+    fn hash_of(shape: &ShapeMinimal) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        shape.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn should_canonicalize_random_polyominoes_in_all_rotations() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for size in 1..=12 {
+            for _ in 0..50 {
+                let points = random_polyomino(&mut rng, size);
+                let expected = ShapeMinimal::new(points.clone());
+
+                for rotation in ROTATIONS8 {
+                    let mut rotated = points.iter().map(|p| rotation * p).collect::<Vec<_>>();
+                    rotated.shuffle(&mut rng);
+                    let actual = ShapeMinimal::new(rotated);
+
+                    assert_eq!(actual, expected, "rotation {:?} of {:?}", rotation, points);
+                    assert_eq!(hash_of(&actual), hash_of(&expected), "rotation {:?} of {:?}", rotation, points);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+use nalgebra::{Rotation2, Vector2};
+
+use crate::poly_2d::lattice::{Hex, King, Lattice, SquareFixed};
+use crate::poly_2d::shape::grid::column_bit;
+use crate::poly_2d::shape::shape_generic::ShapeN;
+
+// like ShapeWithGrid, but for any lattice. only supports counting
+#[derive(Debug)]
+pub struct ShapeOnLattice<L: Lattice> {
+    pub points: Vec<Vector2<i32>>,
+    pub grid: Vec<u64>,
+    lattice: PhantomData<L>,
+}
+
+// polyhexes in axial coordinates, see MOVES_HEX
+pub type ShapeHex = ShapeOnLattice<Hex>;
+
+// fixed polyominoes, which are only translated to the origin rather than canonicalized
+pub type ShapeFixed = ShapeOnLattice<SquareFixed>;
+
+// polyplets, i.e. polyominoes whose cells may also only touch at the corners
+pub type ShapePolyplet = ShapeOnLattice<King>;
+
+impl<L: Lattice> ShapeN<i32, 2> for ShapeOnLattice<L> {
+    fn new(points: Vec<Vector2<i32>>) -> ShapeOnLattice<L> {
+        let grid = canonical_grid_with(&points, L::rotations());
+        ShapeOnLattice { points, grid, lattice: PhantomData }
+    }
+
+    fn points(&self) -> &Vec<Vector2<i32>> {
+        &self.points
+    }
+
+    fn moves() -> &'static [Vector2<i32>] {
+        L::moves()
+    }
+}
+
+// unlike on the square lattice, the rotated bounding box doesn't follow from rotating its corners,
+// so the points are normalized individually
+pub(crate) fn canonical_grid_with(points: &[Vector2<i32>], transforms: &[Rotation2<i32>]) -> Vec<u64> {
+    transforms
+        .iter()
+        .map(|transform| {
+            let transformed: Vec<Vector2<i32>> = points.iter().map(|p| transform * p).collect();
+            let min = Vector2::new(
+                transformed.iter().map(|p| p.x).min().unwrap(),
+                transformed.iter().map(|p| p.y).min().unwrap(),
+            );
+            let max_y = transformed.iter().map(|p| p.y).max().unwrap();
+
+            let mut grid = vec![0; (max_y - min.y) as usize + 1];
+            for p in transformed {
+                let p = p - min;
+                grid[p.y as usize] |= column_bit(p.x)
+            }
+            grid
+        })
+        .min()
+        .unwrap()
+}
+
+impl<L: Lattice> PartialEq for ShapeOnLattice<L> {
+    fn eq(&self, other: &Self) -> bool {
+        self.grid == other.grid
+    }
+}
+
+impl<L: Lattice> Eq for ShapeOnLattice<L> {}
+
+impl<L: Lattice> Hash for ShapeOnLattice<L> {
+    fn hash<H>(&self, state: &mut H)
+        where
+            H: Hasher,
+    {
+        self.grid.hash(state);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use nalgebra::{Matrix2, Rotation2};
+
+    use crate::poly_2d::lattice::{Hex, Lattice};
+    use crate::poly_2d::poly::generate_shapes_up_to_size;
+    use crate::poly_2d::rotation::{REFLECTIONS32, ROTATIONS32};
+    use crate::poly_2d::shape::shape_on_lattice::{canonical_grid_with, ShapeFixed, ShapeHex, ShapePolyplet};
+
+    #[test]
+    fn should_count_fixed_polyominoes() {
+        // https://oeis.org/A001168
+        let expected = [1, 2, 6, 19, 63, 216, 760, 2725];
+        let known_polys = generate_shapes_up_to_size::<ShapeFixed, i32>(expected.len());
+        for (i, count) in expected.iter().enumerate() {
+            assert_eq!(known_polys[&(i + 1)].len(), *count, "n={}", i + 1);
+        }
+    }
+
+    #[test]
+    fn should_count_one_sided_polyhexes() {
+        // https://oeis.org/A006535
+        let expected = [1, 1, 3, 10, 33, 147, 620];
+        let known_polys = generate_shapes_up_to_size::<ShapeHex, i32>(expected.len());
+        for (i, count) in expected.iter().enumerate() {
+            assert_eq!(known_polys[&(i + 1)].len(), *count, "n={}", i + 1);
+        }
+    }
+
+    #[test]
+    fn should_count_free_polyhexes() {
+        // https://oeis.org/A000228
+        let expected = [1, 1, 3, 7, 22, 82, 333];
+
+        // mirroring across q = r, combined with all rotations, gives all symmetries of the hexagon
+        let mirror = Rotation2::from_matrix_unchecked(Matrix2::new(0, 1, 1, 0));
+        let symmetries: Vec<Rotation2<i32>> = Hex::rotations()
+            .iter()
+            .flat_map(|rotation| [*rotation, mirror * rotation])
+            .collect();
+
+        let known_polys = generate_shapes_up_to_size::<ShapeHex, i32>(expected.len());
+        for (i, count) in expected.iter().enumerate() {
+            let free: HashSet<Vec<u64>> = known_polys[&(i + 1)]
+                .iter()
+                .map(|poly| canonical_grid_with(&poly.points, &symmetries))
+                .collect();
+            assert_eq!(free.len(), *count, "n={}", i + 1);
+        }
+    }
+
+    #[test]
+    fn should_count_free_polyplets() {
+        // https://oeis.org/A030222
+        let expected = [1, 2, 5, 22, 94, 524];
+        let symmetries: Vec<Rotation2<i32>> = ROTATIONS32.iter().chain(REFLECTIONS32).copied().collect();
+
+        let known_polys = generate_shapes_up_to_size::<ShapePolyplet, i32>(expected.len());
+        for (i, count) in expected.iter().enumerate() {
+            let free: HashSet<Vec<u64>> = known_polys[&(i + 1)]
+                .iter()
+                .map(|poly| canonical_grid_with(&poly.points, &symmetries))
+                .collect();
+            assert_eq!(free.len(), *count, "n={}", i + 1);
+        }
+    }
+}
@@ -1,10 +1,17 @@
+use std::cmp::Ordering;
 use std::fmt::Display;
 use std::hash::{Hash, Hasher};
 
+use itertools::Itertools;
 use nalgebra::{Rotation2, Vector2};
+use ndarray::Array2;
 
-use crate::poly_2d::rotation::ROTATIONS32;
+use crate::poly_2d::lattice::{Lattice, Square};
 use crate::poly_2d::shape::bounding_box_two_points::BoundingBoxTwoPoints;
+use crate::poly_2d::shape::connectivity::is_connected;
+use crate::poly_2d::shape::grid::{column_bit, populated_width, write_rows, DisplayStyle};
+use crate::poly_2d::shape::shape_error::{ParseError, ShapeError};
+use crate::poly_2d::shape::shape_generic::ShapeN;
 
 #[derive(Debug, Eq)]
 pub struct ShapeWithGrid {
@@ -13,53 +20,541 @@ pub struct ShapeWithGrid {
     pub grid: Vec<u64>,
 }
 
+impl ShapeN<i32, 2> for ShapeWithGrid {
+    fn new(points: Vec<Vector2<i32>>) -> ShapeWithGrid {
+        let (grid_bounds, grid) = canonical_grid(&points);
+        ShapeWithGrid {
+            points,
+            grid_bounds,
+            grid,
+        }
+    }
+
+    fn points(&self) -> &Vec<Vector2<i32>> {
+        &self.points
+    }
+
+    fn moves() -> &'static [Vector2<i32>] {
+        Square::moves()
+    }
+}
+
+// a compact set key for a canonical grid. grids with few enough cells are packed into a single integer,
+// which avoids a heap allocation per shape
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Fingerprint {
+    // the lowest 8 bits hold the width, the rest the rows of the grid, starting with the first row
+    Packed(u128),
+    Grid(Vec<u64>),
+}
+
+// leaves 8 bits for the width
+const MAX_PACKED_CELLS: usize = 120;
+
+impl Fingerprint {
+    pub fn of(grid: &[u64]) -> Fingerprint {
+        let width = populated_width(grid);
+        if width * grid.len() > MAX_PACKED_CELLS {
+            return Fingerprint::Grid(grid.to_vec());
+        }
+
+        let mut bits: u128 = 0;
+        for row in grid.iter().rev() {
+            bits = (bits << width) | *row as u128;
+        }
+        Fingerprint::Packed((bits << 8) | width as u128)
+    }
+
+    pub fn grid(&self) -> Vec<u64> {
+        match self {
+            Fingerprint::Packed(packed) => {
+                let width = (packed & 0xff) as usize;
+                let row_mask = (1u128 << width) - 1;
+                let mut bits = packed >> 8;
+                let mut grid = Vec::new();
+                while bits != 0 {
+                    grid.push((bits & row_mask) as u64);
+                    bits >>= width;
+                }
+                grid
+            }
+            Fingerprint::Grid(grid) => grid.clone(),
+        }
+    }
+}
+
 impl ShapeWithGrid {
-    pub fn canonical(points: Vec<Vector2<i32>>) -> ShapeWithGrid {
-        // TODO cache and extend bounds instead of always recomputing
-        let bounds = BoundingBoxTwoPoints::from(&points);
+    pub fn fingerprint(&self) -> Fingerprint {
+        Fingerprint::of(&self.grid)
+    }
 
-        let mut best: Option<(BoundingBoxTwoPoints, Vec<u64>)> = None;
-        for rotation in ROTATIONS32 {
-            let candidate = rotate_shape(&points, &bounds, rotation);
+    // the way to build a shape from untrusted points. new skips these checks, since the generator only ever grows
+    // valid shapes, and would panic or build a broken grid from anything else
+    pub fn from_points(points: Vec<Vector2<i32>>) -> Result<ShapeWithGrid, ShapeError> {
+        if points.is_empty() {
+            return Err(ShapeError::Empty);
+        }
+        // every rotation has to fit into the bits of a grid row. i64, so that far apart points can't overflow
+        let max_extent = u64::BITS as i64 - 1;
+        for axis in [|p: &Vector2<i32>| p.x, |p: &Vector2<i32>| p.y] {
+            let (min, max) = points.iter().map(axis).minmax().into_option().unwrap();
+            if max as i64 - min as i64 > max_extent {
+                return Err(ShapeError::TooLarge);
+            }
+        }
+        // the grid would absorb them, but the points, and so the size, would be off
+        if !points.iter().all_unique() {
+            return Err(ShapeError::DuplicatePoint);
+        }
+        if !is_connected(&points) {
+            return Err(ShapeError::Disconnected);
+        }
+        Ok(ShapeWithGrid::new(points))
+    }
 
-            match &best {
-                Some(b) => {
-                    if candidate.1 < b.1 {
-                        best = Some(candidate)
-                    }
+    // the distinct images of this shape under all rotations. note that, unlike shapes built with new, their grids
+    // aren't canonical, so they compare unequal to each other
+    pub fn orbit(&self) -> Vec<ShapeWithGrid> {
+        let bounds = BoundingBoxTwoPoints::from(&self.points);
+        let mut orbit: Vec<ShapeWithGrid> = Vec::with_capacity(Square::rotations().len());
+        for rotation in Square::rotations() {
+            let (grid_bounds, grid) = rotate_shape(&self.points, &bounds, rotation);
+            if orbit.iter().any(|image| image.grid == grid) {
+                continue;
+            }
+            orbit.push(ShapeWithGrid {
+                points: grid_points(&grid),
+                grid_bounds,
+                grid,
+            });
+        }
+        orbit
+    }
+
+    // how many distinct images the shape has under the rotations, from 1 for the square to 4 for shapes without any
+    // rotational symmetry. summed over the one-sided polys of a size, this gives the number of fixed ones
+    pub fn rotation_multiplicity(&self) -> usize {
+        self.orbit().len()
+    }
+
+    // the inverse of Display, except that any non-space character counts as occupied
+    pub fn from_ascii(s: &str) -> Result<ShapeWithGrid, ParseError> {
+        let points = s
+            .lines()
+            .enumerate()
+            .flat_map(|(i_y, line)| {
+                line.chars()
+                    .enumerate()
+                    .filter(|(_, c)| *c != ' ')
+                    .map(move |(i_x, _)| Vector2::new(i_x as i32, i_y as i32))
+            })
+            .collect();
+        Ok(ShapeWithGrid::from_points(points)?)
+    }
+
+    // roughly how much memory this shape takes up, including its heap allocations but not the set it's stored in
+    pub fn estimated_bytes(&self) -> usize {
+        size_of::<ShapeWithGrid>() + self.heap_size()
+    }
+
+    // the bytes allocated for points and grid, by capacity rather than length, since that's what they hold on to
+    pub fn heap_size(&self) -> usize {
+        self.points.capacity() * size_of::<Vector2<i32>>() + self.grid.capacity() * size_of::<u64>()
+    }
+
+    pub fn width(&self) -> usize {
+        self.grid_bounds.max_corner().x as usize + 1
+    }
+
+    pub fn height(&self) -> usize {
+        self.grid.len()
+    }
+
+    // the average of the points, i.e. the center of mass of the cells
+    pub fn centroid(&self) -> Vector2<f64> {
+        let sum = self.points.iter().fold(Vector2::zeros(), |sum, p| sum + p.cast::<f64>());
+        sum / self.points.len() as f64
+    }
+
+    // the largest manhattan distance between any two cells
+    pub fn diameter(&self) -> usize {
+        self.points
+            .iter()
+            .flat_map(|a| self.points.iter().map(move |b| ((a.x - b.x).abs() + (a.y - b.y).abs()) as usize))
+            .max()
+            .unwrap_or(0)
+    }
+
+    // the fraction of the bounding box covered by cells
+    pub fn fill_ratio(&self) -> f64 {
+        self.points.len() as f64 / (self.width() * self.height()) as f64
+    }
+
+    // the number of cell edges that aren't shared with another cell
+    pub fn perimeter(&self) -> usize {
+        self.points
+            .iter()
+            .flat_map(|p| Square::moves().iter().map(move |m| p + m))
+            .filter(|neighbor| !self.points.contains(neighbor))
+            .count()
+    }
+
+    // the distinct empty cells next to the shape, i.e. the cells it can grow into
+    pub fn frontier(&self) -> Vec<Vector2<i32>> {
+        let mut frontier: Vec<Vector2<i32>> = Vec::new();
+        for neighbor in self.points.iter().flat_map(|p| Square::moves().iter().map(move |m| p + m)) {
+            if !self.points.contains(&neighbor) && !frontier.contains(&neighbor) {
+                frontier.push(neighbor);
+            }
+        }
+        frontier
+    }
+
+    // the shape without its last cell, in row major order of the canonical grid, that can be removed without
+    // disconnecting it. only depends on the canonical grid, so every shape has exactly one canonical parent, which is
+    // None for a single cell
+    pub fn canonical_parent(&self) -> Option<ShapeWithGrid> {
+        let cells = grid_points(&self.grid);
+        (0..cells.len()).rev().find_map(|i| {
+            let mut rest = cells.clone();
+            rest.remove(i);
+            is_connected(&rest).then(|| ShapeWithGrid::new(rest))
+        })
+    }
+
+    // whether some empty cell is enclosed by the shape, found by flood filling the empty cells from outside of the
+    // bounding box
+    pub fn has_hole(&self) -> bool {
+        // one cell of padding on each side, so the outside is connected
+        let (width, height) = (self.width() as i32 + 2, self.height() as i32 + 2);
+        let occupied = |p: Vector2<i32>| {
+            let (x, y) = (p.x - 1, p.y - 1);
+            (0..width - 2).contains(&x) && (0..height - 2).contains(&y) && (self.grid[y as usize] >> x) & 0x1 != 0
+        };
+        let mut outside = vec![false; (width * height) as usize];
+        let mut stack = vec![Vector2::new(0, 0)];
+        outside[0] = true;
+        let mut reached = 1;
+        while let Some(p) = stack.pop() {
+            for m in Square::moves() {
+                let neighbor = p + m;
+                if neighbor.x < 0 || neighbor.y < 0 || neighbor.x >= width || neighbor.y >= height {
+                    continue;
+                }
+                let i = (neighbor.y * width + neighbor.x) as usize;
+                if !outside[i] && !occupied(neighbor) {
+                    outside[i] = true;
+                    reached += 1;
+                    stack.push(neighbor);
                 }
-                None => best = Some(candidate),
             }
         }
+        let cells: usize = self.grid.iter().map(|row| row.count_ones() as usize).sum();
+        reached + cells < (width * height) as usize
+    }
 
-        let best = best.unwrap();
-        ShapeWithGrid {
-            points,
-            grid_bounds: best.0,
-            grid: best.1,
+    // whether the shape fits into a box of the given size, in any rotation
+    pub fn fits_in(&self, max_width: usize, max_height: usize) -> bool {
+        let (width, height) = (self.width(), self.height());
+        (width <= max_width && height <= max_height) || (height <= max_width && width <= max_height)
+    }
+
+    // like Display, but drawn with the given characters
+    pub fn fmt_with_style(&self, f: &mut std::fmt::Formatter, style: DisplayStyle) -> std::fmt::Result {
+        write_rows(f, &self.grid, populated_width(&self.grid), style)
+    }
+
+    // the occupied cells of the canonical grid as (x, y), in row-major order. unlike points, these don't depend on how
+    // the shape was built
+    pub fn cells(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.grid.iter().enumerate().flat_map(|(i_y, row)| {
+            (0..u64::BITS - row.leading_zeros())
+                .filter(move |i_x| (row >> i_x) & 0x1 != 0)
+                .map(move |i_x| (i_x as i32, i_y as i32))
+        })
+    }
+
+    // the smallest rectangle, as (width, height) with width <= height, that up to max_copies rotated copies of this
+    // shape tile without gaps or overlaps. the smallest by area, and of those the narrowest
+    pub fn tiles_rectangle(&self, max_copies: usize) -> Option<(usize, usize)> {
+        // each rotation as offsets from its first cell in row-major order. grid_points lists cells in that order, so
+        // placing the first cell on the first empty cell of the board covers every way to fill that cell
+        let orientations: Vec<Vec<Vector2<i32>>> = self
+            .orbit()
+            .iter()
+            .map(|image| image.points.iter().map(|p| p - image.points[0]).collect())
+            .collect();
+        let cells = self.points.len();
+        for copies in 1..=max_copies {
+            let area = copies * cells;
+            for width in (1..=area).take_while(|width| width * width <= area) {
+                if area.is_multiple_of(width) && tile(&orientations, width, area / width) {
+                    return Some((width, area / width));
+                }
+            }
+        }
+        None
+    }
+
+    // cells of the canonical grid in row-major order, plus index pairs of 4-adjacent cells. each edge is listed once,
+    // with the smaller index first
+    pub fn to_edge_list(&self) -> (Vec<Vector2<i32>>, Vec<(usize, usize)>) {
+        let nodes = grid_points(&self.grid);
+        let mut edges = Vec::new();
+        for (i, p) in nodes.iter().enumerate() {
+            for step in [Vector2::new(1, 0), Vector2::new(0, 1)] {
+                if let Some(j) = nodes.iter().position(|q| *q == p + step) {
+                    edges.push((i, j));
+                }
+            }
+        }
+        edges.sort();
+        (nodes, edges)
+    }
+
+    // dense 0/1 matrix of the canonical grid, indexed by [[y, x]] so it prints like Display
+    pub fn to_array2(&self) -> Array2<u8> {
+        Array2::from_shape_fn((self.height(), self.width()), |(i_y, i_x)| {
+            ((self.grid[i_y] >> i_x) & 0x1) as u8
+        })
+    }
+
+    // "WxH:" followed by the cells in row major order as hex, most significant bit first and padded to full bytes.
+    // built from the canonical grid, so equal shapes have equal encodings
+    pub fn to_compact(&self) -> String {
+        let hex: String = self.to_packed().iter().map(|byte| format!("{:02x}", byte)).collect();
+        format!("{}x{}:{}", self.width(), self.height(), hex)
+    }
+
+    // "x,y" of each cell of the canonical grid in row major order, separated by spaces. so equal shapes give equal
+    // lines, which sort | uniq can compare
+    pub fn to_coords(&self) -> String {
+        self.cells().map(|(x, y)| format!("{},{}", x, y)).join(" ")
+    }
+
+    // the cells of the grid, row by row, as bits from the most significant down, padded to whole bytes
+    pub fn to_packed(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::with_capacity((self.width() * self.height()).div_ceil(8));
+        let mut i_bit = 0;
+        for row in &self.grid {
+            for i_x in 0..self.width() {
+                if i_bit % 8 == 0 {
+                    bytes.push(0);
+                }
+                if (row >> i_x) & 0x1 != 0 {
+                    *bytes.last_mut().unwrap() |= 0x80 >> (i_bit % 8);
+                }
+                i_bit += 1;
+            }
         }
+        bytes
+    }
+
+    // how many degrees ccw the points were rotated to get the canonical grid. canonical_grid picks by (rows, grid,
+    // bounds), so of several rotations giving the same grid, the bounds decide which one it kept. this reports the
+    // first rotation that gives the grid instead, so symmetric shapes report the smallest angle
+    pub fn canonical_rotation(&self) -> usize {
+        let bounds = BoundingBoxTwoPoints::from(&self.points);
+        let i = Square::rotations()
+            .iter()
+            .position(|rotation| rotate_shape(&self.points, &bounds, rotation).1 == self.grid)
+            .expect("the canonical grid is one of the rotations");
+        i * 360 / Square::rotations().len()
+    }
+
+    // the canonical grid exactly as it's hashed, one hex word per row, for comparing dedup between machines
+    pub fn canonical_dump(&self) -> String {
+        let rows: Vec<String> = self.grid.iter().map(|row| format!("{:016x}", row)).collect();
+        format!(
+            "{}x{} rotation={} grid={}",
+            self.width(),
+            self.height(),
+            self.canonical_rotation(),
+            rows.join(",")
+        )
+    }
+
+    // the inverse of to_compact
+    pub fn from_compact(s: &str) -> Result<ShapeWithGrid, ParseError> {
+        let (size, hex) = s.trim().split_once(':').ok_or(ParseError::Malformed)?;
+        let (width, height) = size.split_once('x').ok_or(ParseError::Malformed)?;
+        let width: usize = width.parse().map_err(|_| ParseError::Malformed)?;
+        let height: usize = height.parse().map_err(|_| ParseError::Malformed)?;
+        let cells = width.checked_mul(height).ok_or(ParseError::Malformed)?;
+        if hex.len() != cells.div_ceil(8) * 2 {
+            return Err(ParseError::Malformed);
+        }
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| hex.get(i..i + 2).and_then(|byte| u8::from_str_radix(byte, 16).ok()))
+            .collect::<Option<Vec<u8>>>()
+            .ok_or(ParseError::Malformed)?;
+        ShapeWithGrid::from_packed(width, height, &bytes)
+    }
+
+    // the inverse of to_packed, given the width and height of the grid
+    pub fn from_packed(width: usize, height: usize, bytes: &[u8]) -> Result<ShapeWithGrid, ParseError> {
+        let cells = width.checked_mul(height).ok_or(ParseError::Malformed)?;
+        if bytes.len() != cells.div_ceil(8) {
+            return Err(ParseError::Malformed);
+        }
+        let points = (0..cells)
+            .filter(|i_bit| bytes[i_bit / 8] & (0x80 >> (i_bit % 8)) != 0)
+            .map(|i_bit| Vector2::new((i_bit % width) as i32, (i_bit / width) as i32))
+            .collect();
+        Ok(ShapeWithGrid::from_points(points)?)
+    }
+
+    // https://conwaylife.com/wiki/Run_Length_Encoded
+    pub fn to_rle(&self) -> String {
+        let rows = self
+            .grid
+            .iter()
+            .map(|row| {
+                let mut runs: Vec<(usize, char)> = Vec::new();
+                for i_x in 0..self.width() {
+                    let tag = if (row >> i_x) & 0x1 != 0 { 'o' } else { 'b' };
+                    match runs.last_mut() {
+                        Some((count, last)) if *last == tag => *count += 1,
+                        _ => runs.push((1, tag)),
+                    }
+                }
+                // trailing dead cells are implied
+                if let Some((_, 'b')) = runs.last() {
+                    runs.pop();
+                }
+                runs.iter()
+                    .map(|(count, tag)| match count {
+                        1 => tag.to_string(),
+                        _ => format!("{}{}", count, tag),
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+
+        format!("x = {}, y = {}\n{}!\n", self.width(), self.height(), rows.join("$"))
     }
 }
 
-fn rotate_shape(
+// the smallest grid over all rotations, which is the same for all rotations of a shape. grids with fewer rows count
+// as smaller, so shapes are stored lying down, with one u64 per row of their shorter side
+pub(crate) fn canonical_grid(points: &Vec<Vector2<i32>>) -> (BoundingBoxTwoPoints, Vec<u64>) {
+    canonical_grid_with(points, Square::rotations())
+}
+
+// rotations that map the shape onto itself give equal grids, but their bounds may have the corners swapped. so ties
+// are broken by the bounds, which makes the result independent of the order of rotations
+fn canonical_grid_with(points: &Vec<Vector2<i32>>, rotations: &[Rotation2<i32>]) -> (BoundingBoxTwoPoints, Vec<u64>) {
+    // TODO cache and extend bounds instead of always recomputing
+    let bounds = BoundingBoxTwoPoints::from(points);
+
+    // every rotation is built into the same scratch grid, which is swapped with the best one when it wins. so there
+    // are only two allocations, rather than one per rotation
+    let extent = bounds.max_corner() - bounds.min_corner();
+    let rows = extent.x.max(extent.y) as usize + 1;
+    let mut best = Vec::with_capacity(rows);
+    let mut best_bounds = rotate_shape_into(points, &bounds, &rotations[0], &mut best);
+    let mut scratch = Vec::with_capacity(rows);
+    for rotation in &rotations[1..] {
+        let candidate_bounds = rotate_shape_into(points, &bounds, rotation, &mut scratch);
+        if (scratch.len(), &scratch, &candidate_bounds) < (best.len(), &best, &best_bounds) {
+            std::mem::swap(&mut best, &mut scratch);
+            best_bounds = candidate_bounds;
+        }
+    }
+
+    // stored shapes shouldn't pay for the scratch capacity
+    best.shrink_to_fit();
+    (best_bounds, best)
+}
+
+// the points of a grid within the given bounds, e.g. to rebuild a shape that was stored as just its canonical grid.
+// canonical grids start at the origin, so for them this is the same as grid_points
+pub fn points_from_grid(bounds: &BoundingBoxTwoPoints, grid: &[u64]) -> Vec<Vector2<i32>> {
+    let offset = bounds.min_corner();
+    grid_points(grid).into_iter().map(|p| p + offset).collect()
+}
+
+// whether the orientations tile a board of the given size exactly, by backtracking over the first empty cell
+fn tile(orientations: &[Vec<Vector2<i32>>], width: usize, height: usize) -> bool {
+    let mut board = vec![false; width * height];
+    fill(orientations, width, height, &mut board, 0)
+}
+
+fn fill(orientations: &[Vec<Vector2<i32>>], width: usize, height: usize, board: &mut [bool], from: usize) -> bool {
+    let Some(first_empty) = (from..board.len()).find(|i| !board[*i]) else {
+        return true;
+    };
+    let anchor = Vector2::new((first_empty % width) as i32, (first_empty / width) as i32);
+    for offsets in orientations {
+        let cells: Option<Vec<usize>> = offsets
+            .iter()
+            .map(|offset| {
+                let p = anchor + offset;
+                if !(0..width as i32).contains(&p.x) || !(0..height as i32).contains(&p.y) {
+                    return None;
+                }
+                let i = p.y as usize * width + p.x as usize;
+                (!board[i]).then_some(i)
+            })
+            .collect();
+        let Some(cells) = cells else {
+            continue;
+        };
+        cells.iter().for_each(|i| board[*i] = true);
+        if fill(orientations, width, height, board, first_empty + 1) {
+            return true;
+        }
+        cells.iter().for_each(|i| board[*i] = false);
+    }
+    false
+}
+
+// the inverse of the grid built by rotate_shape
+pub(crate) fn grid_points(grid: &[u64]) -> Vec<Vector2<i32>> {
+    let mut points = Vec::new();
+    for (i_y, row) in grid.iter().enumerate() {
+        for i_x in 0..u64::BITS - row.leading_zeros() {
+            if (row >> i_x) & 0x1 != 0 {
+                points.push(Vector2::new(i_x as i32, i_y as i32));
+            }
+        }
+    }
+    points
+}
+
+pub(crate) fn rotate_shape(
     points: &Vec<Vector2<i32>>,
     bounds: &BoundingBoxTwoPoints,
     rotation: &Rotation2<i32>,
 ) -> (BoundingBoxTwoPoints, Vec<u64>) {
+    let mut grid = Vec::new();
+    let bounds_rotated_normalized = rotate_shape_into(points, bounds, rotation, &mut grid);
+    (bounds_rotated_normalized, grid)
+}
+
+// like rotate_shape, but overwrites the given grid, reusing its allocation
+fn rotate_shape_into(
+    points: &Vec<Vector2<i32>>,
+    bounds: &BoundingBoxTwoPoints,
+    rotation: &Rotation2<i32>,
+    grid: &mut Vec<u64>,
+) -> BoundingBoxTwoPoints {
     let bounds_rotated = rotation * bounds;
-    let bounds_rotated_min = bounds_rotated.min();
+    let bounds_rotated_min = bounds_rotated.min_corner();
     let bounds_rotated_normalized = bounds_rotated - bounds_rotated_min;
-    let bounds_rotated_normalized_max = bounds_rotated_normalized.max();
+    let bounds_rotated_normalized_max = bounds_rotated_normalized.max_corner();
 
-    let mut grid = vec![0; bounds_rotated_normalized_max.y as usize + 1];
+    grid.clear();
+    grid.resize(bounds_rotated_normalized_max.y as usize + 1, 0);
     for p in points {
         // normalize points to be >= 0 in all axes
         let p = rotation * p - bounds_rotated_min;
         // Row major order, so each row/u64 extends in the x direction. They are indexed in the y direction.
-        grid[p.y as usize] |= 0x1 << p.x
+        grid[p.y as usize] |= column_bit(p.x)
     }
 
-    (bounds_rotated_normalized, grid)
+    bounds_rotated_normalized
 }
 
 impl PartialEq for ShapeWithGrid {
@@ -68,6 +563,19 @@ impl PartialEq for ShapeWithGrid {
     }
 }
 
+// consistent with eq, since the width and height follow from the grid. sorting by them first groups similar shapes
+impl Ord for ShapeWithGrid {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.width(), self.height(), &self.grid).cmp(&(other.width(), other.height(), &other.grid))
+    }
+}
+
+impl PartialOrd for ShapeWithGrid {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Hash for ShapeWithGrid {
     fn hash<H>(&self, state: &mut H)
         where
@@ -77,17 +585,551 @@ impl Hash for ShapeWithGrid {
     }
 }
 
+// as wide as the grid's occupied cells, rather than trusting grid_bounds, which may not match a hand-built grid
 impl Display for ShapeWithGrid {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        for row in &self.grid {
-            for i_x in 0..self.grid_bounds.max().x + 1 {
-                let present = (row >> i_x) & 0x1 != 0;
-                write!(f, "{}", if present { 'O' } else { ' ' })?;
-            }
-            writeln!(f)?;
-        }
+        self.fmt_with_style(f, DisplayStyle::default())
+    }
+}
 
-        Ok(())
+// displays a shape with the given style, e.g. format!("{}", Styled(&shape, style))
+pub struct Styled<'a>(pub &'a ShapeWithGrid, pub DisplayStyle);
+
+impl Display for Styled<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.0.fmt_with_style(f, self.1)
     }
 }
 
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use itertools::Itertools;
+    use nalgebra::Vector2;
+
+    use crate::poly_2d::poly::generate_shapes_up_to_size_sequential;
+    use crate::poly_2d::rotation::ROTATIONS32;
+    use crate::poly_2d::shape::bounding_box_two_points::BoundingBoxTwoPoints;
+    use crate::poly_2d::shape::connectivity::is_connected;
+    use crate::poly_2d::shape::grid::DisplayStyle;
+    use crate::poly_2d::shape::shape_error::{ParseError, ShapeError};
+    use crate::poly_2d::shape::shape_generic::ShapeN;
+    use crate::poly_2d::shape::shape_with_grid::{
+        canonical_grid, canonical_grid_with, grid_points, points_from_grid, Fingerprint, ShapeWithGrid, Styled,
+    };
+
+    #[test]
+    fn should_encode_rle() {
+        // plus pentomino
+        let shape = ShapeWithGrid::new(vec![
+            Vector2::new(1, 0),
+            Vector2::new(0, 1),
+            Vector2::new(1, 1),
+            Vector2::new(2, 1),
+            Vector2::new(1, 2),
+        ]);
+        assert_eq!(shape.to_rle(), "x = 3, y = 3\nbo$3o$bo!\n");
+    }
+
+    #[test]
+    fn should_find_centroid_at_center_of_symmetric_shape() {
+        // plus pentomino, centered on (1, 1)
+        let plus = ShapeWithGrid::new(vec![
+            Vector2::new(1, 0),
+            Vector2::new(0, 1),
+            Vector2::new(1, 1),
+            Vector2::new(2, 1),
+            Vector2::new(1, 2),
+        ]);
+        assert_eq!(plus.centroid(), Vector2::new(1.0, 1.0));
+
+        // domino, centered between its cells
+        let domino = ShapeWithGrid::new(vec![Vector2::new(0, 0), Vector2::new(1, 0)]);
+        assert_eq!(domino.centroid(), Vector2::new(0.5, 0.0));
+    }
+
+    #[test]
+    fn should_measure_diameter_and_fill_ratio() {
+        let cell = ShapeWithGrid::new(vec![Vector2::new(0, 0)]);
+        assert_eq!(cell.diameter(), 0);
+        assert_eq!(cell.fill_ratio(), 1.0);
+
+        let straight = ShapeWithGrid::new((0..4).map(|x| Vector2::new(x, 0)).collect());
+        assert_eq!(straight.diameter(), 3);
+        assert_eq!(straight.fill_ratio(), 1.0);
+
+        let square = ShapeWithGrid::new(vec![Vector2::new(0, 0), Vector2::new(1, 0), Vector2::new(0, 1), Vector2::new(1, 1)]);
+        assert_eq!(square.diameter(), 2);
+        assert_eq!(square.fill_ratio(), 1.0);
+
+        // L tetromino, 4 cells in a 2x3 box, from one end to the other corner
+        let l = ShapeWithGrid::new(vec![Vector2::new(0, 0), Vector2::new(0, 1), Vector2::new(0, 2), Vector2::new(1, 0)]);
+        assert_eq!(l.diameter(), 3);
+        assert_eq!(l.fill_ratio(), 4.0 / 6.0);
+    }
+
+    #[test]
+    fn should_rebuild_same_shape_from_grid() {
+        let known_polys = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(7);
+        for poly in known_polys.values().flatten() {
+            let points = points_from_grid(&poly.grid_bounds, &poly.grid);
+            assert_eq!(points.len(), poly.points.len());
+            let rebuilt = ShapeWithGrid::new(points);
+            assert_eq!(rebuilt.grid, poly.grid);
+        }
+
+        // bounds away from the origin move the points along
+        let bounds = BoundingBoxTwoPoints { p0: Vector2::new(2, 3), p1: Vector2::new(3, 3) };
+        assert_eq!(points_from_grid(&bounds, &[0b11]), vec![Vector2::new(2, 3), Vector2::new(3, 3)]);
+    }
+
+    #[test]
+    fn should_display_rows_as_wide_as_grid() {
+        // standing up, but canonically lying down, so width and height swap
+        let l = ShapeWithGrid::new(vec![Vector2::new(0, 0), Vector2::new(0, 1), Vector2::new(0, 2), Vector2::new(-1, 2)]);
+        assert_eq!((l.width(), l.height()), (3, 2));
+        let displayed = l.to_string();
+        let rows: Vec<&str> = displayed.lines().collect();
+        assert_eq!(rows.len(), l.height());
+        assert!(rows.iter().all(|row| row.len() == l.width()), "{:?}", rows);
+        // the last column is occupied somewhere, so it wasn't padded
+        assert!(rows.iter().any(|row| row.ends_with('O')), "{:?}", rows);
+
+        // bounds with the corners the other way around, as left by rotations
+        let swapped = ShapeWithGrid {
+            points: vec![],
+            grid_bounds: BoundingBoxTwoPoints { p0: Vector2::new(2, 0), p1: Vector2::new(0, 1) },
+            grid: vec![0b111, 0b100],
+        };
+        assert_eq!(swapped.to_string(), "OOO\n  O\n");
+    }
+
+    #[test]
+    fn should_encode_rle_runs() {
+        //  OOO
+        // OO
+        let shape = ShapeWithGrid {
+            points: vec![],
+            grid_bounds: BoundingBoxTwoPoints {
+                p0: Vector2::new(0, 0),
+                p1: Vector2::new(3, 1),
+            },
+            grid: vec![0b1110, 0b0011],
+        };
+        assert_eq!(shape.to_rle(), "x = 4, y = 2\nb3o$2o!\n");
+    }
+
+    #[test]
+    fn should_build_connected_from_points() {
+        let shape = ShapeWithGrid::from_points(vec![Vector2::new(0, 0), Vector2::new(0, 1)]);
+        assert_eq!(shape, Ok(ShapeWithGrid::new(vec![Vector2::new(0, 0), Vector2::new(0, 1)])));
+    }
+
+    #[test]
+    fn should_not_build_disconnected_or_empty_from_points() {
+        assert_eq!(
+            ShapeWithGrid::from_points(vec![Vector2::new(0, 0), Vector2::new(1, 1)]),
+            Err(ShapeError::Disconnected)
+        );
+        assert_eq!(ShapeWithGrid::from_points(vec![]), Err(ShapeError::Empty));
+    }
+
+    #[test]
+    fn should_measure_heap_size_by_capacity() {
+        let p_pentomino = shape(&[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]);
+        assert!(p_pentomino.heap_size() >= 5 * 8 + 2 * 8, "{}", p_pentomino.heap_size());
+        assert_eq!(p_pentomino.estimated_bytes(), size_of::<ShapeWithGrid>() + p_pentomino.heap_size());
+
+        // twice the points in the same single row
+        let bar = |n| shape(&(0..n).map(|x| (x, 0)).collect::<Vec<_>>());
+        assert_eq!(bar(10).heap_size() - bar(5).heap_size(), 5 * 8);
+
+        let mut grown = bar(5);
+        grown.points.reserve_exact(10);
+        assert_eq!(grown.heap_size(), bar(5).heap_size() + 10 * 8);
+    }
+
+    #[test]
+    fn should_count_distinct_rotations() {
+        assert_eq!(shape(&[(0, 0), (1, 0), (0, 1), (1, 1)]).rotation_multiplicity(), 1);
+        assert_eq!(shape(&[(0, 0), (1, 0)]).rotation_multiplicity(), 2);
+        assert_eq!(shape(&[(0, 0), (1, 0), (0, 1)]).rotation_multiplicity(), 4);
+    }
+
+    #[test]
+    fn should_display_with_style() {
+        let s = shape(&[(1, 0), (2, 0), (0, 1), (1, 1)]);
+        let style = DisplayStyle { filled: '#', empty: '.', ..Default::default() };
+        assert_eq!(Styled(&s, style).to_string(), ".##\n##.\n");
+        // the default style is what Display uses
+        assert_eq!(Styled(&s, DisplayStyle::default()).to_string(), s.to_string());
+    }
+
+    #[test]
+    fn should_display_mirrored_vertically_with_y_up() {
+        // the L tetromino has no mirror symmetry, so flipping it gives a different picture
+        let l = shape(&[(0, 0), (0, 1), (0, 2), (1, 0)]);
+        let y_up = Styled(&l, DisplayStyle { y_up: true, ..Default::default() }).to_string();
+        assert_ne!(y_up, l.to_string());
+        assert_eq!(y_up.lines().collect::<Vec<_>>(), l.to_string().lines().rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn should_iterate_cells_of_canonical_grid() {
+        let s = shape(&[(4, 4), (4, 5), (5, 5), (5, 6)]);
+        let cells: Vec<(i32, i32)> = s.cells().collect();
+        assert_eq!(cells, grid_points(&s.grid).iter().map(|p| (p.x, p.y)).collect::<Vec<_>>());
+        assert_eq!(shape(&cells), s);
+    }
+
+    #[test]
+    fn should_store_long_thin_shapes_lying_down() {
+        let standing = shape(&(0..60).map(|y| (0, y)).collect::<Vec<_>>());
+        let lying = shape(&(0..60).map(|x| (x, 3)).collect::<Vec<_>>());
+        assert_eq!(standing.grid, vec![(1 << 60) - 1]);
+        assert_eq!((standing.width(), standing.height()), (60, 1));
+        assert_eq!(standing, lying);
+
+        // an L that's taller than wide lies down too
+        let l = shape(&[(0, 0), (0, 1), (0, 2), (0, 3), (1, 0)]);
+        assert_eq!(l.grid.len(), 2);
+        assert_eq!(l, shape(&[(0, 0), (1, 0), (2, 0), (3, 0), (3, 1)]));
+    }
+
+    #[test]
+    fn should_tile_rectangle_with_l_trominoes() {
+        let l = ShapeWithGrid::from_ascii("O\nOO").unwrap();
+        assert_eq!(l.tiles_rectangle(1), None);
+        assert_eq!(l.tiles_rectangle(2), Some((2, 3)));
+    }
+
+    #[test]
+    fn should_tile_smallest_rectangle_or_none() {
+        // a straight tromino is its own 1x3 rectangle
+        let bar = ShapeWithGrid::from_ascii("OOO").unwrap();
+        assert_eq!(bar.tiles_rectangle(2), Some((1, 3)));
+        // the S tetromino tiles no rectangle, while 4 T tetrominoes make a 4x4 square
+        assert_eq!(ShapeWithGrid::from_ascii(" OO\nOO").unwrap().tiles_rectangle(6), None);
+        assert_eq!(ShapeWithGrid::from_ascii("OOO\n O").unwrap().tiles_rectangle(4), Some((4, 4)));
+        // the L tetromino needs 2 copies for a 2x4 rectangle
+        assert_eq!(ShapeWithGrid::from_ascii("O\nO\nOO").unwrap().tiles_rectangle(2), Some((2, 4)));
+    }
+
+    #[test]
+    fn should_build_valid_shape_from_points() {
+        let points = vec![Vector2::new(0, 0), Vector2::new(1, 0), Vector2::new(1, 1)];
+        assert_eq!(ShapeWithGrid::from_points(points.clone()), Ok(ShapeWithGrid::new(points)));
+        // as wide as a grid row allows
+        let bar: Vec<Vector2<i32>> = (0..64).map(|i_x| Vector2::new(i_x - 32, 7)).collect();
+        assert!(ShapeWithGrid::from_points(bar).is_ok());
+    }
+
+    #[test]
+    fn should_not_build_shape_from_invalid_points() {
+        assert_eq!(ShapeWithGrid::from_points(vec![]), Err(ShapeError::Empty));
+        assert_eq!(
+            ShapeWithGrid::from_points(vec![Vector2::new(0, 0), Vector2::new(0, 2)]),
+            Err(ShapeError::Disconnected)
+        );
+        let bar: Vec<Vector2<i32>> = (0..65).map(|i_y| Vector2::new(0, i_y)).collect();
+        assert_eq!(ShapeWithGrid::from_points(bar), Err(ShapeError::TooLarge));
+        assert_eq!(
+            ShapeWithGrid::from_points(vec![Vector2::new(0, 0), Vector2::new(0, 0), Vector2::new(1, 0)]),
+            Err(ShapeError::DuplicatePoint)
+        );
+        // checked before connectivity, whose neighbors of these points would overflow
+        assert_eq!(
+            ShapeWithGrid::from_points(vec![Vector2::new(i32::MIN, 0), Vector2::new(i32::MAX, 0)]),
+            Err(ShapeError::TooLarge)
+        );
+    }
+
+    #[test]
+    fn should_canonicalize_symmetric_shapes_regardless_of_rotation_order() {
+        let square = vec![Vector2::new(0, 0), Vector2::new(1, 0), Vector2::new(0, 1), Vector2::new(1, 1)];
+        let expected = canonical_grid(&square);
+        for rotations in ROTATIONS32.iter().copied().permutations(ROTATIONS32.len()) {
+            assert_eq!(canonical_grid_with(&square, &rotations), expected, "{:?}", rotations);
+        }
+    }
+
+    #[test]
+    fn should_fingerprint_rotations_the_same() {
+        // L tetromino, and the same rotated by 90 degrees
+        let l = ShapeWithGrid::new(vec![
+            Vector2::new(0, 0),
+            Vector2::new(0, 1),
+            Vector2::new(0, 2),
+            Vector2::new(1, 0),
+        ]);
+        let l_rotated = ShapeWithGrid::new(vec![
+            Vector2::new(2, 1),
+            Vector2::new(0, 0),
+            Vector2::new(2, 0),
+            Vector2::new(1, 0),
+        ]);
+        assert_eq!(l.fingerprint(), l_rotated.fingerprint());
+        assert!(matches!(l.fingerprint(), Fingerprint::Packed(_)));
+    }
+
+    #[test]
+    fn should_fingerprint_distinct_shapes_differently() {
+        // same cells in row major order, but different widths
+        let bar = ShapeWithGrid::new((0..4).map(|x| Vector2::new(x, 0)).collect());
+        let square = ShapeWithGrid::new(vec![
+            Vector2::new(0, 0),
+            Vector2::new(1, 0),
+            Vector2::new(0, 1),
+            Vector2::new(1, 1),
+        ]);
+        assert_ne!(bar.fingerprint(), square.fingerprint());
+    }
+
+    #[test]
+    fn should_fall_back_to_grid_fingerprint_for_large_shapes() {
+        // 11x11 bounding box
+        let big_l = ShapeWithGrid::new((0..11).map(|x| Vector2::new(x, 0)).chain((1..11).map(|y| Vector2::new(0, y))).collect());
+        assert_eq!(big_l.fingerprint(), Fingerprint::Grid(big_l.grid.clone()));
+        assert_eq!(big_l.fingerprint().grid(), big_l.grid);
+    }
+
+    #[test]
+    fn should_restore_grid_from_fingerprint() {
+        let l = ShapeWithGrid::new(vec![
+            Vector2::new(0, 0),
+            Vector2::new(1, 0),
+            Vector2::new(1, 1),
+        ]);
+        assert_eq!(l.fingerprint().grid(), l.grid);
+    }
+
+    fn shape(points: &[(i32, i32)]) -> ShapeWithGrid {
+        ShapeWithGrid::new(points.iter().map(|&(x, y)| Vector2::new(x, y)).collect())
+    }
+
+    #[test]
+    fn should_have_orbit_of_one_for_fully_symmetric_shapes() {
+        assert_eq!(shape(&[(0, 0)]).orbit().len(), 1);
+        assert_eq!(shape(&[(0, 0), (1, 0), (0, 1), (1, 1)]).orbit().len(), 1);
+    }
+
+    #[test]
+    fn should_have_orbit_sizes() {
+        assert_eq!(shape(&[(0, 0), (1, 0)]).orbit().len(), 2);
+        assert_eq!(shape(&[(1, 0), (2, 0), (0, 1), (1, 1)]).orbit().len(), 2);
+        assert_eq!(shape(&[(0, 0), (0, 1), (0, 2), (1, 0)]).orbit().len(), 4);
+    }
+
+    #[test]
+    fn should_canonicalize_orbit_back_to_shape() {
+        let l = shape(&[(0, 0), (0, 1), (0, 2), (1, 0)]);
+        for image in l.orbit() {
+            assert_eq!(ShapeWithGrid::new(image.points), l);
+        }
+    }
+
+    #[test]
+    fn should_find_holes() {
+        let ring = shape(&[(0, 0), (1, 0), (2, 0), (0, 1), (2, 1), (0, 2), (1, 2), (2, 2)]);
+        assert!(ring.has_hole());
+        let open_ring = shape(&[(0, 0), (1, 0), (2, 0), (0, 1), (2, 1), (0, 2), (2, 2)]);
+        assert!(!open_ring.has_hole());
+        let u = shape(&[(0, 0), (2, 0), (0, 1), (1, 1), (2, 1)]);
+        assert!(!u.has_hole());
+        assert!(!shape(&[(0, 0)]).has_hole());
+    }
+
+    #[test]
+    fn should_order_by_size_then_grid() {
+        let domino = shape(&[(0, 0), (1, 0)]);
+        let bar = shape(&[(0, 0), (1, 0), (2, 0)]);
+        let l = shape(&[(0, 0), (1, 0), (0, 1)]);
+        let l_rotated = shape(&[(0, 0), (1, 0), (1, 1)]);
+        assert!(domino < bar);
+        // canonical shapes lie down, so the bar is wider than the L
+        assert!(l < bar);
+        assert_eq!(l.cmp(&l_rotated), std::cmp::Ordering::Equal);
+    }
+
+    #[test]
+    fn should_parse_ascii() {
+        let t = ShapeWithGrid::from_ascii("###\n # \n").unwrap();
+        assert_eq!(t, shape(&[(0, 0), (1, 0), (2, 0), (1, 1)]));
+    }
+
+    #[test]
+    fn should_round_trip_ascii() {
+        for poly in &generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(6)[&6] {
+            assert_eq!(&ShapeWithGrid::from_ascii(&poly.to_string()).unwrap(), poly, "{}", poly);
+        }
+    }
+
+    #[test]
+    fn should_not_parse_empty_or_disconnected_ascii() {
+        assert_eq!(ShapeWithGrid::from_ascii(""), Err(ParseError::Empty));
+        assert_eq!(ShapeWithGrid::from_ascii("  \n  "), Err(ParseError::Empty));
+        assert_eq!(ShapeWithGrid::from_ascii("O O"), Err(ParseError::Disconnected));
+        assert_eq!(ShapeWithGrid::from_ascii("O\n\nO"), Err(ParseError::Disconnected));
+    }
+
+    #[test]
+    fn should_list_edges_of_square() {
+        let (nodes, edges) = shape(&[(0, 0), (1, 0), (0, 1), (1, 1)]).to_edge_list();
+        assert_eq!(
+            nodes,
+            vec![Vector2::new(0, 0), Vector2::new(1, 0), Vector2::new(0, 1), Vector2::new(1, 1)]
+        );
+        assert_eq!(edges, vec![(0, 1), (0, 2), (1, 3), (2, 3)]);
+    }
+
+    #[test]
+    fn should_list_edges_of_canonical_orientation() {
+        // a vertical bar is listed in its canonical, horizontal orientation
+        let (nodes, edges) = shape(&[(5, 5), (5, 6), (5, 7)]).to_edge_list();
+        assert_eq!(nodes, vec![Vector2::new(0, 0), Vector2::new(1, 0), Vector2::new(2, 0)]);
+        assert_eq!(edges, vec![(0, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn should_encode_compact() {
+        // plus pentomino, 010 111 010
+        let plus = shape(&[(1, 0), (0, 1), (1, 1), (2, 1), (1, 2)]);
+        assert_eq!(plus.to_compact(), "3x3:5d00");
+        assert_eq!(shape(&[(0, 0)]).to_compact(), "1x1:80");
+    }
+
+    #[test]
+    fn should_encode_rotations_the_same_compact() {
+        let l = shape(&[(0, 0), (0, 1), (0, 2), (1, 0)]);
+        let l_rotated = shape(&[(2, 1), (0, 0), (2, 0), (1, 0)]);
+        assert_eq!(l.to_compact(), l_rotated.to_compact());
+        assert_ne!(l.to_compact(), shape(&[(0, 0), (1, 0), (2, 0), (3, 0)]).to_compact());
+    }
+
+    #[test]
+    fn should_dump_canonical_grid() {
+        let l = shape(&[(0, 0), (0, 1), (0, 2), (1, 0)]);
+        assert_eq!(l.canonical_dump(), "3x2 rotation=270 grid=0000000000000001,0000000000000007");
+        // the same shape, given in its canonical orientation
+        let l_canonical = shape(&[(0, 0), (0, 1), (1, 1), (2, 1)]);
+        assert_eq!(l_canonical.canonical_dump(), "3x2 rotation=0 grid=0000000000000001,0000000000000007");
+        assert_eq!(shape(&[(0, 0)]).canonical_dump(), "1x1 rotation=0 grid=0000000000000001");
+    }
+
+    #[test]
+    fn should_encode_coords() {
+        // V tromino, in every rotation
+        let v = [(0, 0), (1, 0), (0, 1)];
+        for rotation in ROTATIONS32 {
+            let rotated = shape(&v.map(|(x, y)| {
+                let p = rotation * Vector2::new(x, y);
+                (p.x, p.y)
+            }));
+            // O
+            // OO
+            assert_eq!(rotated.to_coords(), "0,0 0,1 1,1");
+        }
+    }
+
+    #[test]
+    fn should_round_trip_compact() {
+        for poly in &generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(6)[&6] {
+            assert_eq!(&ShapeWithGrid::from_compact(&poly.to_compact()).unwrap(), poly, "{}", poly);
+        }
+    }
+
+    #[test]
+    fn should_not_parse_malformed_compact() {
+        assert_eq!(ShapeWithGrid::from_compact("3x3"), Err(ParseError::Malformed));
+        assert_eq!(ShapeWithGrid::from_compact("3x3:5d"), Err(ParseError::Malformed));
+        assert_eq!(ShapeWithGrid::from_compact("3x3:5g00"), Err(ParseError::Malformed));
+        assert_eq!(ShapeWithGrid::from_compact("2x1:00"), Err(ParseError::Empty));
+        assert_eq!(ShapeWithGrid::from_compact("3x1:a0"), Err(ParseError::Disconnected));
+        // more cells than fit in a usize
+        assert_eq!(ShapeWithGrid::from_compact("99999999999x99999999999:"), Err(ParseError::Malformed));
+        assert_eq!(ShapeWithGrid::from_packed(usize::MAX, 2, &[]), Err(ParseError::Malformed));
+    }
+
+    #[test]
+    fn should_export_to_array2() {
+        let l = shape(&[(0, 0), (0, 1), (0, 2), (1, 0)]);
+        let array = l.to_array2();
+
+        assert_eq!(array.dim(), (l.height(), l.width()));
+        assert_eq!(array.sum() as usize, l.points.len());
+        for ((i_y, i_x), cell) in array.indexed_iter() {
+            assert_eq!(*cell == 1, (l.grid[i_y] >> i_x) & 0x1 != 0);
+        }
+    }
+
+    #[test]
+    fn should_have_perimeter() {
+        assert_eq!(shape(&[(0, 0)]).perimeter(), 4);
+        // the free tetrominoes I, O, T, S, L
+        assert_eq!(shape(&[(0, 0), (1, 0), (2, 0), (3, 0)]).perimeter(), 10);
+        assert_eq!(shape(&[(0, 0), (1, 0), (0, 1), (1, 1)]).perimeter(), 8);
+        assert_eq!(shape(&[(0, 0), (1, 0), (2, 0), (1, 1)]).perimeter(), 10);
+        assert_eq!(shape(&[(1, 0), (2, 0), (0, 1), (1, 1)]).perimeter(), 10);
+        assert_eq!(shape(&[(0, 0), (0, 1), (0, 2), (1, 0)]).perimeter(), 10);
+    }
+
+    #[test]
+    fn should_have_frontier() {
+        // 3 cells on each long side, and 1 at each end
+        let bar = shape(&[(0, 0), (1, 0), (2, 0)]);
+        assert_eq!(bar.frontier().len(), 8);
+        // the inner corner of the L neighbors two cells, but is only listed once
+        let l = shape(&[(0, 0), (1, 0), (0, 1)]);
+        assert_eq!(l.frontier().len(), 7);
+        assert!(l.frontier().contains(&Vector2::new(1, 1)));
+        assert!(l.frontier().iter().all(|p| !l.points.contains(p)));
+    }
+
+    #[test]
+    fn should_have_canonical_parent() {
+        assert_eq!(shape(&[(0, 0)]).canonical_parent(), None);
+        assert_eq!(shape(&[(0, 0), (1, 0)]).canonical_parent(), Some(shape(&[(0, 0)])));
+        // canonically, the S is upright, and its bottom cell is removed
+        let s = shape(&[(1, 0), (2, 0), (0, 1), (1, 1)]);
+        assert_eq!(s.canonical_parent(), Some(shape(&[(0, 0), (1, 0), (1, 1)])));
+        // the cells of the U next to its bottom would disconnect it
+        let u = shape(&[(0, 0), (2, 0), (0, 1), (1, 1), (2, 1)]);
+        let parent = u.canonical_parent().unwrap();
+        assert_eq!(parent.points.len(), 4);
+        assert!(is_connected(&parent.points));
+        // the same for every rotation
+        for image in s.orbit() {
+            assert_eq!(ShapeWithGrid::new(image.points).canonical_parent(), s.canonical_parent());
+        }
+    }
+
+    #[test]
+    fn should_fit_in_box_in_any_rotation() {
+        let l = shape(&[(0, 0), (0, 1), (0, 2), (1, 0)]);
+        assert!(l.fits_in(2, 3));
+        assert!(l.fits_in(3, 2));
+        assert!(!l.fits_in(2, 2));
+        assert!(!l.fits_in(1, 4));
+    }
+
+    #[test]
+    fn should_return_the_same_static_moves_every_call() {
+        assert!(std::ptr::eq(ShapeWithGrid::moves(), ShapeWithGrid::moves()));
+    }
+
+    #[test]
+    fn should_move_to_4_unit_neighbors() {
+        let moves: HashSet<Vector2<i32>> = ShapeWithGrid::moves().iter().copied().collect();
+        assert_eq!(ShapeWithGrid::moves().len(), 4);
+        assert_eq!(
+            moves,
+            HashSet::from([
+                Vector2::new(0, 1),
+                Vector2::new(0, -1),
+                Vector2::new(1, 0),
+                Vector2::new(-1, 0),
+            ])
+        );
+    }
+}
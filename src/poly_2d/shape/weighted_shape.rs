@@ -0,0 +1,106 @@
+use std::hash::{Hash, Hasher};
+
+use nalgebra::{Rotation2, Vector2};
+
+use crate::poly_2d::rotation::ROTATIONS32;
+use crate::poly_2d::shape::shape_generic::ShapeN;
+use crate::poly_2d::shape::shape_with_grid::ShapeWithGrid;
+
+// a shape whose cells each carry a weight, e.g. a color or a score. equality and hashing only look at the shape, so a
+// set keeps one of each shape regardless of weights
+#[derive(Debug)]
+pub struct WeightedShape {
+    pub shape: ShapeWithGrid,
+    // the weight of each of shape.points, in the same order
+    pub weights: Vec<u8>,
+}
+
+impl WeightedShape {
+    pub fn new(cells: Vec<(Vector2<i32>, u8)>) -> WeightedShape {
+        let (points, weights) = cells.into_iter().unzip();
+        WeightedShape { shape: ShapeWithGrid::new(points), weights }
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = (Vector2<i32>, u8)> + '_ {
+        self.shape.points.iter().copied().zip(self.weights.iter().copied())
+    }
+
+    pub fn weight_sum(&self) -> u64 {
+        self.weights.iter().map(|weight| *weight as u64).sum()
+    }
+
+    // the same shape, with each weight still on the cell it was on
+    pub fn rotated(&self, rotation: &Rotation2<i32>) -> WeightedShape {
+        WeightedShape::new(self.cells().map(|(p, weight)| (rotation * p, weight)).collect())
+    }
+
+    // the cells in the orientation of the canonical grid, moved to the origin and sorted by row. symmetric shapes have
+    // several such orientations, and this picks the one canonical_rotation does
+    pub fn canonical_cells(&self) -> Vec<(Vector2<i32>, u8)> {
+        let rotation = &ROTATIONS32[self.shape.canonical_rotation() / 90];
+        let rotated: Vec<(Vector2<i32>, u8)> = self.cells().map(|(p, weight)| (rotation * p, weight)).collect();
+        let min = Vector2::new(
+            rotated.iter().map(|(p, _)| p.x).min().unwrap_or(0),
+            rotated.iter().map(|(p, _)| p.y).min().unwrap_or(0),
+        );
+        let mut cells: Vec<(Vector2<i32>, u8)> = rotated.into_iter().map(|(p, weight)| (p - min, weight)).collect();
+        cells.sort_by_key(|(p, _)| (p.y, p.x));
+        cells
+    }
+}
+
+impl PartialEq for WeightedShape {
+    fn eq(&self, other: &Self) -> bool {
+        self.shape == other.shape
+    }
+}
+
+impl Eq for WeightedShape {}
+
+impl Hash for WeightedShape {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.shape.hash(state);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use nalgebra::Vector2;
+
+    use crate::poly_2d::rotation::ROTATIONS32;
+    use crate::poly_2d::shape::weighted_shape::WeightedShape;
+
+    // L tetromino with the given weight on the foot and 1 everywhere else
+    fn l(foot: u8) -> WeightedShape {
+        WeightedShape::new(vec![
+            (Vector2::new(0, 0), 1),
+            (Vector2::new(0, 1), 1),
+            (Vector2::new(0, 2), 1),
+            (Vector2::new(1, 0), foot),
+        ])
+    }
+
+    #[test]
+    fn should_dedup_by_shape_but_keep_weights() {
+        let (light, heavy) = (l(1), l(9).rotated(&ROTATIONS32[1]));
+        assert_eq!(light, heavy);
+        assert_eq!((light.weight_sum(), heavy.weight_sum()), (4, 12));
+
+        let set: HashSet<WeightedShape> = HashSet::from([light, heavy]);
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn should_carry_weights_through_rotation() {
+        for rotation in ROTATIONS32 {
+            let rotated = l(9).rotated(rotation);
+            // the foot is still where the weight is
+            assert!(rotated.cells().any(|(p, weight)| p == rotation * Vector2::new(1, 0) && weight == 9));
+            // and ends up on the same cell of the canonical orientation from any rotation
+            assert_eq!(rotated.canonical_cells(), l(9).canonical_cells(), "{:?}", rotation);
+        }
+        assert_ne!(l(9).canonical_cells(), l(1).canonical_cells());
+    }
+}
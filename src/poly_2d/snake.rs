@@ -1,16 +1,61 @@
 use std::cmp::{max, min};
+use std::collections::BTreeMap;
 
 use ndarray::*;
-use rand::{seq::SliceRandom, thread_rng};
+use rand::rngs::StdRng;
+use rand::{random, seq::SliceRandom, SeedableRng};
+use rayon::prelude::*;
 
 use super::common::{BoundingBox, Shape};
+use super::shape::shape_error::ShapeError;
 
-pub fn generate_snake_2d(n: usize) {
-    let shape = grow_random_snake_2d(n);
+// grows count snakes of up to n cells and prints the longest, along with how many reached each length. each walk has
+// its own rng, seeded with the base seed plus its index, so the same seed gives the same walks with or without
+// parallel
+pub fn generate_snake_2d(n: usize, count: usize, parallel: bool, seed: Option<u64>) -> Result<(), ShapeError> {
+    if n == 0 || count == 0 {
+        return Err(ShapeError::Empty);
+    }
+    let seed = seed.unwrap_or_else(random);
+    let lengths = snake_lengths(n, count, parallel, seed);
+
+    // the first of the longest, so that the result doesn't depend on the order walks finished in
+    let (longest, length) = lengths.iter().enumerate().rev().max_by_key(|(_, length)| **length).unwrap();
+    if count > 1 {
+        println!("longest: {} of {}", length, n);
+        for (length, walks) in length_histogram(&lengths) {
+            println!("length {: >3}: {}", length, walks);
+        }
+    }
+    let (shape, _) = grow_random_snake_2d(n, &mut walk_rng(seed, longest));
     println!("{:?}", shape.crop());
+    Ok(())
+}
+
+fn walk_rng(seed: u64, walk: usize) -> StdRng {
+    StdRng::seed_from_u64(seed.wrapping_add(walk as u64))
 }
 
-fn grow_random_snake_2d(size: usize) -> Shape {
+// the length each walk reached before it was done or trapped, in the order of the walks
+fn snake_lengths(n: usize, count: usize, parallel: bool, seed: u64) -> Vec<usize> {
+    let length = |walk: usize| grow_random_snake_2d(n, &mut walk_rng(seed, walk)).1;
+    if parallel {
+        (0..count).into_par_iter().map(length).collect()
+    } else {
+        (0..count).map(length).collect()
+    }
+}
+
+fn length_histogram(lengths: &[usize]) -> BTreeMap<usize, usize> {
+    let mut histogram = BTreeMap::new();
+    for length in lengths {
+        *histogram.entry(*length).or_insert(0) += 1;
+    }
+    histogram
+}
+
+// grows until the snake has size cells or has no free neighbor left, and returns how many cells it got to
+fn grow_random_snake_2d(size: usize, rng: &mut StdRng) -> (Shape, usize) {
     // allow enough space to grow linearly in any direction
     let grid_size = size * 2 - 1;
     let mut grid = Array2::<u8>::zeros((grid_size, grid_size));
@@ -30,8 +75,7 @@ fn grow_random_snake_2d(size: usize) -> Shape {
         // decide random direction
         // TODO optimize constant shuffling
         let mut directions: Vec<(isize, isize)> = vec![(0, 1), (0, -1), (1, 0), (-1, 0)];
-        let mut rng = thread_rng();
-        directions.shuffle(&mut rng);
+        directions.shuffle(rng);
 
         let new_location: Option<(usize, usize)> = directions
             .iter()
@@ -41,9 +85,12 @@ fn grow_random_snake_2d(size: usize) -> Shape {
                     (location.1 as isize + direction.1) as usize,
                 )
             })
-            .find(|new_location| grid[*new_location] == 0);
+            .find(|new_location| grid.get(*new_location) == Some(&0));
 
-        location = new_location.expect("no available moves");
+        location = match new_location {
+            Some(new_location) => new_location,
+            None => return (Shape { grid, bounds }, i - 1),
+        };
 
         // grow in that direction
         grid[location] = i as u8;
@@ -55,5 +102,34 @@ fn grow_random_snake_2d(size: usize) -> Shape {
         bounds.max_y = max(bounds.max_y, location.1);
     }
 
-    Shape { grid, bounds }
+    (Shape { grid, bounds }, size)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::poly_2d::snake::{grow_random_snake_2d, length_histogram, snake_lengths, walk_rng};
+
+    #[test]
+    fn should_find_same_lengths_in_parallel_and_sequentially() {
+        let sequential = snake_lengths(40, 64, false, 7);
+        let parallel = snake_lengths(40, 64, true, 7);
+        assert_eq!(length_histogram(&parallel), length_histogram(&sequential));
+        assert_eq!(parallel, sequential);
+    }
+
+    #[test]
+    fn should_stop_when_trapped() {
+        // long walks get trapped sooner or later
+        let lengths = snake_lengths(200, 32, false, 0);
+        assert!(lengths.iter().all(|length| (2..=200).contains(length)));
+        assert!(lengths.iter().any(|length| *length < 200));
+    }
+
+    #[test]
+    fn should_grow_as_many_cells_as_reported() {
+        for walk in 0..16 {
+            let (shape, length) = grow_random_snake_2d(50, &mut walk_rng(3, walk));
+            assert_eq!(shape.grid.iter().filter(|cell| **cell != 0).count(), length);
+        }
+    }
 }
@@ -0,0 +1,495 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Display;
+
+use clap::ValueEnum;
+use nalgebra::Rotation2;
+
+use crate::poly_2d::poly_error::PolyError;
+use crate::poly_2d::rotation::{REFLECTIONS32, ROTATIONS32};
+use crate::poly_2d::shape::bounding_box_two_points::BoundingBoxTwoPoints;
+use crate::poly_2d::shape::shape_with_grid::{grid_points, rotate_shape, ShapeWithGrid};
+
+// the subgroup of D4 that maps a shape onto itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, ValueEnum)]
+pub enum SymmetryClass {
+    // identity only
+    #[value(alias = "asym")]
+    Asymmetric,
+    // 180 deg rotation
+    C2,
+    // 90 deg rotation
+    C4,
+    // a single mirror axis
+    D1,
+    // two mirror axes and 180 deg rotation
+    D2,
+    // all symmetries of the square
+    D4,
+}
+
+pub static SYMMETRY_CLASSES: &[SymmetryClass] = &[
+    SymmetryClass::Asymmetric,
+    SymmetryClass::C2,
+    SymmetryClass::C4,
+    SymmetryClass::D1,
+    SymmetryClass::D2,
+    SymmetryClass::D4,
+];
+
+impl SymmetryClass {
+    // how many of the 4 rotations map the shape onto itself
+    pub fn rotation_order(&self) -> usize {
+        match self {
+            SymmetryClass::Asymmetric | SymmetryClass::D1 => 1,
+            SymmetryClass::C2 | SymmetryClass::D2 => 2,
+            SymmetryClass::C4 | SymmetryClass::D4 => 4,
+        }
+    }
+}
+
+impl SymmetryClass {
+    // whether a shape of this class also has all symmetries of the other class. mirror axes count as the same whether
+    // they're along the grid or diagonal
+    pub fn contains(&self, other: SymmetryClass) -> bool {
+        use SymmetryClass::*;
+        match (self, other) {
+            (_, Asymmetric) => true,
+            (D4, _) => true,
+            (C4 | D2, C2) => true,
+            (D2, D1) => true,
+            (class, other) => *class == other,
+        }
+    }
+}
+
+impl Display for SymmetryClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            SymmetryClass::Asymmetric => "asym",
+            SymmetryClass::C2 => "C2",
+            SymmetryClass::C4 => "C4",
+            SymmetryClass::D1 => "D1",
+            SymmetryClass::D2 => "D2",
+            SymmetryClass::D4 => "D4",
+        })
+    }
+}
+
+// which transformations make two shapes count as the same
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymmetryGroup {
+    // translations only
+    Fixed,
+    // and rotations, which is what ShapeWithGrid::new canonicalizes under
+    OneSided,
+    // and reflections
+    Free,
+}
+
+impl SymmetryGroup {
+    pub fn transforms(&self) -> impl Iterator<Item = &'static Rotation2<i32>> {
+        let (rotations, reflections): (&[_], &[_]) = match self {
+            SymmetryGroup::Fixed => (&ROTATIONS32[..1], &[]),
+            SymmetryGroup::OneSided => (ROTATIONS32, &[]),
+            SymmetryGroup::Free => (ROTATIONS32, REFLECTIONS32),
+        };
+        rotations.iter().chain(reflections)
+    }
+}
+
+impl ShapeWithGrid {
+    // whether some transformation in the group maps other onto this shape. unlike ==, this doesn't rely on both
+    // grids being canonical under the same group, e.g. if one came from free_representative
+    pub fn is_same_up_to_symmetry(&self, other: &ShapeWithGrid, group: SymmetryGroup) -> bool {
+        if self.points.len() != other.points.len() {
+            return false;
+        }
+        let (_, grid) = rotate_shape(&self.points, &BoundingBoxTwoPoints::from(&self.points), &ROTATIONS32[0]);
+        let other_bounds = BoundingBoxTwoPoints::from(&other.points);
+        group.transforms().any(|transform| rotate_shape(&other.points, &other_bounds, transform).1 == grid)
+    }
+}
+
+// a shape together with its images under all of D4, each computed once, for asking several symmetry questions about
+// the same shape. not part of ShapeWithGrid, since generated shapes mostly live too short to pay for the images
+pub struct AnalyzedShape<'a> {
+    pub shape: &'a ShapeWithGrid,
+    // the bounds and grid of the shape under each of ROTATIONS32, then each of REFLECTIONS32
+    images: Vec<(BoundingBoxTwoPoints, Vec<u64>)>,
+}
+
+impl AnalyzedShape<'_> {
+    pub fn new(shape: &ShapeWithGrid) -> AnalyzedShape<'_> {
+        let bounds = BoundingBoxTwoPoints::from(&shape.points);
+        let images = ROTATIONS32
+            .iter()
+            .chain(REFLECTIONS32)
+            .map(|transform| rotate_shape(&shape.points, &bounds, transform))
+            .collect();
+        AnalyzedShape { shape, images }
+    }
+
+    pub fn stabilizer(&self) -> Vec<&'static Rotation2<i32>> {
+        let (_, grid) = &self.images[0];
+        ROTATIONS32
+            .iter()
+            .chain(REFLECTIONS32)
+            .zip(&self.images)
+            .filter(|(_, (_, image))| image == grid)
+            .map(|(transform, _)| transform)
+            .collect()
+    }
+
+    pub fn symmetry_class(&self) -> SymmetryClass {
+        let stabilizer = self.stabilizer();
+        let has_rotation = |rotation| stabilizer.contains(&rotation);
+        match stabilizer.len() {
+            8 => SymmetryClass::D4,
+            4 if has_rotation(&ROTATIONS32[1]) => SymmetryClass::C4,
+            4 => SymmetryClass::D2,
+            2 if has_rotation(&ROTATIONS32[2]) => SymmetryClass::C2,
+            2 => SymmetryClass::D1,
+            _ => SymmetryClass::Asymmetric,
+        }
+    }
+
+    // the same as ShapeWithGrid::rotation_multiplicity
+    pub fn rotation_multiplicity(&self) -> usize {
+        let rotations = &self.images[..ROTATIONS32.len()];
+        rotations
+            .iter()
+            .enumerate()
+            .filter(|(i, (_, grid))| rotations[..*i].iter().all(|(_, earlier)| earlier != grid))
+            .count()
+    }
+
+    pub fn free_representative(&self) -> ShapeWithGrid {
+        let (grid_bounds, grid) = self.images.iter().min_by(|(_, grid1), (_, grid2)| grid1.cmp(grid2)).unwrap();
+        ShapeWithGrid {
+            points: grid_points(grid),
+            grid_bounds: grid_bounds.clone(),
+            grid: grid.clone(),
+        }
+    }
+}
+
+pub fn stabilizer(shape: &ShapeWithGrid) -> Vec<&'static Rotation2<i32>> {
+    AnalyzedShape::new(shape).stabilizer()
+}
+
+pub fn symmetry_class(shape: &ShapeWithGrid) -> SymmetryClass {
+    AnalyzedShape::new(shape).symmetry_class()
+}
+
+// like ShapeWithGrid::new, but canonical under reflections too, so mirror images compare equal
+pub fn free_representative(shape: &ShapeWithGrid) -> ShapeWithGrid {
+    AnalyzedShape::new(shape).free_representative()
+}
+
+// the same as symmetry_class, but rotating the shape again for every transform instead of caching its images in an
+// AnalyzedShape. kept to check and benchmark the cached images against
+pub fn symmetry_class_uncached(shape: &ShapeWithGrid) -> SymmetryClass {
+    let bounds = BoundingBoxTwoPoints::from(&shape.points);
+    let (_, grid) = rotate_shape(&shape.points, &bounds, &ROTATIONS32[0]);
+    let stabilizer: Vec<_> = ROTATIONS32
+        .iter()
+        .chain(REFLECTIONS32)
+        .filter(|transform| rotate_shape(&shape.points, &bounds, transform).1 == grid)
+        .collect();
+    let has_rotation = |rotation| stabilizer.contains(&rotation);
+    match stabilizer.len() {
+        8 => SymmetryClass::D4,
+        4 if has_rotation(&ROTATIONS32[1]) => SymmetryClass::C4,
+        4 => SymmetryClass::D2,
+        2 if has_rotation(&ROTATIONS32[2]) => SymmetryClass::C2,
+        2 => SymmetryClass::D1,
+        _ => SymmetryClass::Asymmetric,
+    }
+}
+
+// the same as free_representative, but without an AnalyzedShape, see symmetry_class_uncached
+pub fn free_representative_uncached(shape: &ShapeWithGrid) -> ShapeWithGrid {
+    let bounds = BoundingBoxTwoPoints::from(&shape.points);
+    let (grid_bounds, grid) = ROTATIONS32
+        .iter()
+        .chain(REFLECTIONS32)
+        .map(|transform| rotate_shape(&shape.points, &bounds, transform))
+        .min_by(|(_, grid1), (_, grid2)| grid1.cmp(grid2))
+        .unwrap();
+    ShapeWithGrid {
+        points: grid_points(&grid),
+        grid_bounds,
+        grid,
+    }
+}
+
+// collapses shapes that are rotations or reflections of each other, e.g. to get the free polys from a run that
+// didn't distinguish them
+pub fn to_free(fixed: &HashSet<ShapeWithGrid>) -> HashSet<ShapeWithGrid> {
+    fixed.iter().map(free_representative).collect()
+}
+
+// pairs of distinct polys, smaller first, where one is a rotation of the other. there are none if all polys are
+// canonical, so any pair points to a canonicalization bug
+pub fn rotated_duplicates(polys: &HashSet<ShapeWithGrid>) -> Vec<(&ShapeWithGrid, &ShapeWithGrid)> {
+    let mut duplicates = Vec::new();
+    for poly in polys {
+        for image in poly.orbit() {
+            // the set compares by grid, so this finds whichever poly has the image's grid
+            if let Some(other) = polys.get(&image) {
+                if poly < other {
+                    duplicates.push((poly, other));
+                }
+            }
+        }
+    }
+    duplicates.sort();
+    duplicates
+}
+
+pub fn count_symmetry_classes(polys: &HashSet<ShapeWithGrid>) -> BTreeMap<SymmetryClass, usize> {
+    let mut counts = BTreeMap::new();
+    for poly in polys {
+        *counts.entry(symmetry_class(poly)).or_insert(0) += 1;
+    }
+    counts
+}
+
+pub fn report_symmetry_classes(max_n: usize, known_polys: &HashMap<usize, HashSet<ShapeWithGrid>>) {
+    print!("{: >4}", "n");
+    for class in SYMMETRY_CLASSES {
+        print!(" {: >10}", class.to_string());
+    }
+    println!(" {: >10} {: >10}", "total", "fixed");
+
+    for n in 1..=max_n {
+        let counts = count_symmetry_classes(&known_polys[&n]);
+        print!("{: >4}", n);
+        for class in SYMMETRY_CLASSES {
+            print!(" {: >10}", counts.get(class).unwrap_or(&0));
+        }
+        println!(" {: >10} {: >10}", counts.values().sum::<usize>(), fixed_count(&counts));
+    }
+}
+
+// drops the polys of every size that don't have at least the symmetries of the given class
+pub fn retain_symmetry(known_polys: &mut HashMap<usize, HashSet<ShapeWithGrid>>, class: SymmetryClass) {
+    for polys in known_polys.values_mut() {
+        polys.retain(|poly| symmetry_class(poly).contains(class));
+    }
+}
+
+pub fn report_retained_symmetry(max_n: usize, known_polys: &HashMap<usize, HashSet<ShapeWithGrid>>, class: SymmetryClass) {
+    println!("{: >4} {: >10}", "n", format!("{}+", class));
+    for n in 1..=max_n {
+        println!("{: >4} {: >10}", n, known_polys[&n].len());
+    }
+}
+
+// each poly stands for as many fixed polys as it has distinct rotations
+pub fn fixed_count(counts: &BTreeMap<SymmetryClass, usize>) -> usize {
+    counts.iter().map(|(class, count)| count * 4 / class.rotation_order()).sum()
+}
+
+// the number of one-sided polys among the given fixed ones, by Burnside's lemma: a fixed poly is left alone by as
+// many rotations as its multiplicity divides into, and the sum of that over all fixed polys is the number of
+// one-sided polys times the number of rotations. so this never compares grids, unlike canonicalizing
+pub fn burnside_one_sided_count(fixed_multiplicities: impl IntoIterator<Item = usize>) -> usize {
+    let rotations = ROTATIONS32.len();
+    let fixed_by_rotations: usize = fixed_multiplicities.into_iter().map(|multiplicity| rotations / multiplicity).sum();
+    fixed_by_rotations / rotations
+}
+
+// fails if the one-sided polys of size n, found by canonicalizing, aren't as many as Burnside's lemma says there are
+// for the fixed polys of that size with the given multiplicities
+pub fn check_burnside(
+    n: usize,
+    fixed_multiplicities: impl IntoIterator<Item = usize>,
+    one_sided: &HashSet<ShapeWithGrid>,
+) -> Result<(), PolyError> {
+    let expected = burnside_one_sided_count(fixed_multiplicities);
+    if expected != one_sided.len() {
+        return Err(PolyError::Burnside(n, expected, one_sided.len()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use nalgebra::Vector2;
+
+    use crate::poly_2d::poly::generate_shapes_up_to_size_sequential;
+    use crate::poly_2d::poly_error::PolyError;
+    use crate::poly_2d::shape::shape_on_lattice::ShapeFixed;
+    use crate::poly_2d::shape::shape_generic::ShapeN;
+    use crate::poly_2d::shape::shape_with_grid::ShapeWithGrid;
+    use crate::poly_2d::symmetry::{
+        burnside_one_sided_count, check_burnside, count_symmetry_classes, fixed_count, free_representative,
+        free_representative_uncached, retain_symmetry, rotated_duplicates, symmetry_class, symmetry_class_uncached,
+        to_free, AnalyzedShape, SymmetryClass, SymmetryGroup, SYMMETRY_CLASSES,
+    };
+
+    fn shape(points: &[(i32, i32)]) -> ShapeWithGrid {
+        ShapeWithGrid::new(points.iter().map(|&(x, y)| Vector2::new(x, y)).collect())
+    }
+
+    #[test]
+    fn should_classify_tetrominoes() {
+        assert_eq!(symmetry_class(&shape(&[(0, 0), (1, 0), (0, 1), (1, 1)])), SymmetryClass::D4);
+        assert_eq!(symmetry_class(&shape(&[(0, 0), (1, 0), (2, 0), (3, 0)])), SymmetryClass::D2);
+        assert_eq!(symmetry_class(&shape(&[(0, 0), (1, 0), (2, 0), (1, 1)])), SymmetryClass::D1);
+        assert_eq!(symmetry_class(&shape(&[(1, 0), (2, 0), (0, 1), (1, 1)])), SymmetryClass::C2);
+        assert_eq!(symmetry_class(&shape(&[(0, 0), (0, 1), (0, 2), (1, 0)])), SymmetryClass::Asymmetric);
+    }
+
+    #[test]
+    fn should_classify_c4() {
+        // pinwheel-like shape with only rotational symmetry
+        //  O
+        //  OOO
+        // OOO
+        //   O
+        let pinwheel = shape(&[(1, 0), (1, 1), (2, 1), (3, 1), (0, 2), (1, 2), (2, 2), (2, 3)]);
+        assert_eq!(symmetry_class(&pinwheel), SymmetryClass::C4);
+    }
+
+    #[test]
+    fn should_only_retain_polys_invariant_under_quarter_turns() {
+        let mut known_polys = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(9);
+        let all = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(9);
+        retain_symmetry(&mut known_polys, SymmetryClass::C4);
+        for n in 1..=9 {
+            for poly in &known_polys[&n] {
+                // a quarter turn maps the poly onto itself, so its orbit is just itself
+                assert!(poly.orbit().iter().all(|image| image == poly), "n={} {:?}", n, poly);
+            }
+            let expected = all[&n].iter().filter(|poly| poly.orbit().iter().all(|image| image == *poly)).count();
+            assert_eq!(known_polys[&n].len(), expected, "n={}", n);
+        }
+        // the cell, the square, and the plus
+        assert_eq!(known_polys[&1].len(), 1);
+        assert_eq!(known_polys[&4].len(), 1);
+        assert_eq!(known_polys[&5].len(), 1);
+        assert_eq!(known_polys[&6].len(), 0);
+    }
+
+    #[test]
+    fn should_contain_own_and_smaller_classes() {
+        for class in SYMMETRY_CLASSES {
+            assert!(class.contains(*class), "{}", class);
+            assert!(class.contains(SymmetryClass::Asymmetric), "{}", class);
+            assert!(SymmetryClass::D4.contains(*class), "{}", class);
+        }
+        assert!(SymmetryClass::C4.contains(SymmetryClass::C2));
+        assert!(!SymmetryClass::C4.contains(SymmetryClass::D1));
+        assert!(!SymmetryClass::D2.contains(SymmetryClass::C4));
+        assert!(!SymmetryClass::C2.contains(SymmetryClass::D1));
+    }
+
+    #[test]
+    fn should_sum_symmetry_classes_to_fixed_count() {
+        // https://oeis.org/A001168
+        let fixed = [1, 2, 6, 19, 63, 216, 760, 2725];
+        let known_polys = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(fixed.len());
+        for (i, expected) in fixed.iter().enumerate() {
+            let polys = &known_polys[&(i + 1)];
+            let counts = count_symmetry_classes(polys);
+            assert_eq!(counts.values().sum::<usize>(), polys.len());
+            assert_eq!(fixed_count(&counts), *expected, "n={}", i + 1);
+        }
+    }
+
+    #[test]
+    fn should_find_no_rotated_duplicates_among_generated_polys() {
+        let known_polys = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(8);
+        for n in 1..=8 {
+            assert!(rotated_duplicates(&known_polys[&n]).is_empty(), "n={}", n);
+        }
+    }
+
+    #[test]
+    fn should_find_mis_canonicalized_poly() {
+        let mut tetrominoes = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(4).remove(&4).unwrap();
+        let l = shape(&[(0, 0), (0, 1), (0, 2), (1, 0)]);
+        // an orbit image keeps its rotation rather than being canonicalized
+        let l_rotated = l.orbit().into_iter().find(|image| image.grid != l.grid).unwrap();
+        let l_rotated_grid = l_rotated.grid.clone();
+        assert!(tetrominoes.insert(l_rotated));
+
+        let duplicates = rotated_duplicates(&tetrominoes);
+        assert_eq!(duplicates.len(), 1);
+        let (a, b) = duplicates[0];
+        assert_eq!(HashSet::from([&a.grid, &b.grid]), HashSet::from([&l.grid, &l_rotated_grid]));
+    }
+
+    #[test]
+    fn should_collapse_fixed_tetrominoes_to_free() {
+        // orbit images keep their orientation, so together they are the fixed tetrominoes
+        let fixed: HashSet<ShapeWithGrid> = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(4)[&4]
+            .iter()
+            .flat_map(|poly| poly.orbit())
+            .collect();
+        assert_eq!(fixed.len(), 19);
+
+        let free = to_free(&fixed);
+        assert_eq!(free.len(), 5);
+
+        // S and Z are mirror images
+        let s_and_z = HashSet::from([shape(&[(1, 0), (2, 0), (0, 1), (1, 1)]), shape(&[(0, 0), (1, 0), (1, 1), (2, 1)])]);
+        assert_eq!(to_free(&s_and_z).len(), 1);
+    }
+
+    #[test]
+    fn should_match_mirror_image_only_under_free_group() {
+        let s = shape(&[(1, 0), (2, 0), (0, 1), (1, 1)]);
+        let z = shape(&[(0, 0), (1, 0), (1, 1), (2, 1)]);
+        assert!(!s.is_same_up_to_symmetry(&z, SymmetryGroup::Fixed));
+        assert!(!s.is_same_up_to_symmetry(&z, SymmetryGroup::OneSided));
+        assert!(s.is_same_up_to_symmetry(&z, SymmetryGroup::Free));
+    }
+
+    #[test]
+    fn should_match_rotation_unless_fixed() {
+        // an L and the same L turned by 90 deg, both moved away from the origin
+        let l = shape(&[(5, 5), (5, 6), (5, 7), (6, 5)]);
+        let turned = shape(&[(-3, 2), (-4, 2), (-5, 2), (-3, 3)]);
+        assert!(l.is_same_up_to_symmetry(&l, SymmetryGroup::Fixed));
+        assert!(!l.is_same_up_to_symmetry(&turned, SymmetryGroup::Fixed));
+        assert!(l.is_same_up_to_symmetry(&turned, SymmetryGroup::OneSided));
+        // a free representative isn't canonical under rotations, but is still the same shape
+        assert!(l.is_same_up_to_symmetry(&free_representative(&turned), SymmetryGroup::Free));
+    }
+
+    #[test]
+    fn should_count_one_sided_polys_by_burnside() {
+        let fixed = generate_shapes_up_to_size_sequential::<ShapeFixed, i32>(7);
+        let one_sided = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(7);
+        let multiplicities =
+            |n| fixed[&n].iter().map(|poly: &ShapeFixed| ShapeWithGrid::new(poly.points().clone()).rotation_multiplicity());
+        for n in 1..=7 {
+            assert!(check_burnside(n, multiplicities(n), &one_sided[&n]).is_ok(), "n={}", n);
+        }
+        assert_eq!(burnside_one_sided_count(multiplicities(7)), 196);
+
+        // one poly missing from the one-sided set
+        let mut one_sided = one_sided;
+        let missing = one_sided.get_mut(&5).unwrap();
+        let some_poly = missing.iter().next().unwrap().fingerprint();
+        missing.retain(|poly| poly.fingerprint() != some_poly);
+        assert!(matches!(check_burnside(5, multiplicities(5), missing), Err(PolyError::Burnside(5, 18, 17))));
+    }
+
+    #[test]
+    fn should_answer_the_same_from_cached_images() {
+        let known_polys = generate_shapes_up_to_size_sequential::<ShapeWithGrid, i32>(7);
+        for poly in known_polys.values().flatten() {
+            let analyzed = AnalyzedShape::new(poly);
+            assert_eq!(analyzed.rotation_multiplicity(), poly.rotation_multiplicity(), "{}", poly);
+            assert_eq!(analyzed.rotation_multiplicity(), 4 / analyzed.symmetry_class().rotation_order(), "{}", poly);
+            assert_eq!(analyzed.symmetry_class(), symmetry_class_uncached(poly), "{}", poly);
+            assert_eq!(analyzed.free_representative().grid, free_representative_uncached(poly).grid, "{}", poly);
+        }
+    }
+}
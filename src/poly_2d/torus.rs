@@ -0,0 +1,187 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use dashmap::DashSet;
+use rayon::prelude::*;
+
+use crate::cli::TorusSize;
+use crate::poly_2d::poly::{report_performance, report_size_start, GenerationStats};
+
+// polyominoes on a torus, whose cells wrap around at the edges. each shape is a mask with bit y * width + x set for
+// each cell (x, y), so the torus can have at most 64 cells. shapes are canonical under translation only, since
+// rotating a torus that isn't square doesn't map it onto itself
+pub fn generate_torus_shapes_up_to_size_with_stats(
+    max_n: usize,
+    torus: TorusSize,
+) -> (HashMap<usize, HashSet<u64>>, Vec<GenerationStats>) {
+    let mut known_polys: HashMap<usize, HashSet<u64>> = HashMap::new();
+    let mut stats = Vec::with_capacity(max_n);
+    for n in 1..=max_n.min(torus.cells()) {
+        let (polys, size_stats) = generate_torus_shapes_with_size(n, &known_polys, torus);
+        known_polys.insert(n, polys);
+        stats.push(size_stats);
+    }
+    (known_polys, stats)
+}
+
+fn generate_torus_shapes_with_size(
+    n: usize,
+    known_polys: &HashMap<usize, HashSet<u64>>,
+    torus: TorusSize,
+) -> (HashSet<u64>, GenerationStats) {
+    let start = Instant::now();
+    report_size_start(n);
+
+    if n == 1 {
+        let stats = GenerationStats {
+            size: n,
+            elapsed: start.elapsed(),
+            points_tried: 1,
+            polys_tried: 1,
+            found: 1,
+            profile: None,
+            children: None,
+        };
+        report_performance(&stats);
+        return (HashSet::from([1]), stats);
+    }
+
+    let new_polys: DashSet<u64> = DashSet::new();
+    let points_tried = AtomicUsize::new(0);
+    let polys_tried = AtomicUsize::new(0);
+    known_polys[&(n - 1)].par_iter().for_each(|&prev_poly| {
+        let mut parent_points_tried = 0;
+        let mut parent_polys_tried = 0;
+        for cell in cells(prev_poly) {
+            for neighbor in torus.neighbors(cell) {
+                parent_points_tried += 1;
+                if prev_poly & (1 << neighbor) != 0 {
+                    continue;
+                }
+
+                parent_polys_tried += 1;
+                new_polys.insert(torus.canonical(prev_poly | (1 << neighbor)));
+            }
+        }
+        points_tried.fetch_add(parent_points_tried, Ordering::Relaxed);
+        polys_tried.fetch_add(parent_polys_tried, Ordering::Relaxed);
+    });
+
+    let new_polys: HashSet<u64> = new_polys.into_iter().collect();
+    let stats = GenerationStats {
+        size: n,
+        elapsed: start.elapsed(),
+        points_tried: points_tried.into_inner(),
+        polys_tried: polys_tried.into_inner(),
+        found: new_polys.len(),
+        profile: None,
+        children: None,
+    };
+    report_performance(&stats);
+    (new_polys, stats)
+}
+
+// indices of the set bits
+fn cells(mask: u64) -> impl Iterator<Item = usize> {
+    (0..u64::BITS as usize).filter(move |i| mask & (1 << i) != 0)
+}
+
+impl TorusSize {
+    fn cells(&self) -> usize {
+        self.width * self.height
+    }
+
+    // the cells sharing an edge with the given one, wrapping around. on tori 1 or 2 cells wide or high, the same
+    // cell may be listed more than once, or be its own neighbor
+    fn neighbors(&self, cell: usize) -> [usize; 4] {
+        let (x, y) = (cell % self.width, cell / self.width);
+        let (w, h) = (self.width, self.height);
+        [
+            y * w + (x + 1) % w,
+            y * w + (x + w - 1) % w,
+            (y + 1) % h * w + x,
+            (y + h - 1) % h * w + x,
+        ]
+    }
+
+    fn translate(&self, mask: u64, dx: usize, dy: usize) -> u64 {
+        cells(mask)
+            .map(|cell| {
+                let (x, y) = ((cell % self.width + dx) % self.width, (cell / self.width + dy) % self.height);
+                1 << (y * self.width + x)
+            })
+            .fold(0, |translated, bit| translated | bit)
+    }
+
+    // the smallest mask over all translations
+    fn canonical(&self, mask: u64) -> u64 {
+        (0..self.height)
+            .flat_map(|dy| (0..self.width).map(move |dx| (dx, dy)))
+            .map(|(dx, dy)| self.translate(mask, dx, dy))
+            .min()
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::cli::TorusSize;
+    use crate::poly_2d::torus::{cells, generate_torus_shapes_up_to_size_with_stats};
+
+    // whether the cells of the mask are connected on the torus, by flood fill
+    fn is_connected(torus: TorusSize, mask: u64) -> bool {
+        let first = cells(mask).next().unwrap();
+        let mut visited: u64 = 1 << first;
+        let mut stack = vec![first];
+        while let Some(cell) = stack.pop() {
+            for neighbor in torus.neighbors(cell) {
+                if mask & (1 << neighbor) != 0 && visited & (1 << neighbor) == 0 {
+                    visited |= 1 << neighbor;
+                    stack.push(neighbor);
+                }
+            }
+        }
+        visited == mask
+    }
+
+    #[test]
+    fn should_find_all_connected_subsets_of_3x3_torus() {
+        let torus = TorusSize { width: 3, height: 3 };
+        let (known_polys, stats) = generate_torus_shapes_up_to_size_with_stats(9, torus);
+        assert_eq!(stats.len(), 9);
+
+        // brute force over all subsets of the 9 cells
+        for n in 1..=9 {
+            let expected: HashSet<u64> = (1..1u64 << 9)
+                .filter(|mask| mask.count_ones() as usize == n && is_connected(torus, *mask))
+                .map(|mask| torus.canonical(mask))
+                .collect();
+            assert_eq!(known_polys[&n], expected, "n={}", n);
+        }
+        // a single cell, a horizontal and a vertical domino, and the whole torus with and without a cell
+        assert_eq!(known_polys[&1].len(), 1);
+        assert_eq!(known_polys[&2].len(), 2);
+        assert_eq!(known_polys[&8].len(), 1);
+        assert_eq!(known_polys[&9].len(), 1);
+    }
+
+    #[test]
+    fn should_wrap_around_edges() {
+        let torus = TorusSize { width: 3, height: 2 };
+        // (0, 0) and (2, 0) are neighbors across the edge
+        assert!(is_connected(torus, 0b000101));
+        assert_eq!(torus.canonical(0b000101), torus.canonical(0b000011));
+        // (0, 0) and (0, 1) are neighbors both ways around
+        assert_eq!(torus.neighbors(0)[2], torus.neighbors(0)[3]);
+    }
+
+    #[test]
+    fn should_stop_at_torus_size() {
+        let (known_polys, stats) = generate_torus_shapes_up_to_size_with_stats(10, TorusSize { width: 2, height: 2 });
+        assert_eq!(stats.len(), 4);
+        assert_eq!(known_polys[&4], HashSet::from([0b1111]));
+    }
+}
@@ -0,0 +1,94 @@
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Instant;
+
+// whether progress goes to a single line that's repainted in place, rather than one line per size. only ever on when
+// stdout is a terminal, since \r means nothing in a file
+static ENABLED: AtomicBool = AtomicBool::new(false);
+// whether the line has been painted since the last newline
+static PAINTED: AtomicBool = AtomicBool::new(false);
+
+// how often workers may repaint the line
+const REPAINT_MS: u64 = 100;
+
+pub fn set_status_line(enabled: bool) {
+    ENABLED.store(enabled && io::stdout().is_terminal(), Ordering::Relaxed);
+}
+
+pub fn status_line() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+// overwrites the current line, clearing it first so that a shorter line hides the end of a longer one
+pub fn paint(line: std::fmt::Arguments) {
+    print!("\r\x1b[K{}", line);
+    io::stdout().flush().ok();
+    PAINTED.store(true, Ordering::Relaxed);
+}
+
+// ends the painted line, if any, so what's printed next starts on a line of its own
+pub fn finish() {
+    if PAINTED.swap(false, Ordering::Relaxed) {
+        println!();
+    }
+}
+
+// the live status of one size. workers call repaint as they find polys, and at most one of them actually repaints
+// every REPAINT_MS
+pub struct SizeStatus {
+    n: usize,
+    start: Instant,
+    last_paint_ms: AtomicU64,
+}
+
+impl SizeStatus {
+    pub fn new(n: usize, start: Instant) -> SizeStatus {
+        SizeStatus { n, start, last_paint_ms: AtomicU64::new(0) }
+    }
+
+    pub fn repaint(&self, found: usize) {
+        let elapsed_ms = self.start.elapsed().as_millis() as u64;
+        let last_paint_ms = self.last_paint_ms.load(Ordering::Relaxed);
+        if elapsed_ms < last_paint_ms + REPAINT_MS {
+            return;
+        }
+        // only the worker that wins the exchange paints, the others carry on
+        if self
+            .last_paint_ms
+            .compare_exchange(last_paint_ms, elapsed_ms, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+        {
+            paint(format_args!(
+                "size: {: >2}... time: {:.1}s found: {}",
+                self.n,
+                elapsed_ms as f64 / 1000.0,
+                found
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use crate::status_line::{set_status_line, SizeStatus};
+
+    #[test]
+    fn should_not_enable_status_line_without_terminal() {
+        // cargo test captures stdout, so it's never a terminal here
+        set_status_line(true);
+        assert!(!super::status_line());
+    }
+
+    #[test]
+    fn should_only_repaint_after_interval() {
+        let status = SizeStatus::new(3, Instant::now() - Duration::from_secs(1));
+        status.repaint(1);
+        let first = status.last_paint_ms.load(std::sync::atomic::Ordering::Relaxed);
+        assert!(first >= 1000);
+        // too soon after the first
+        status.repaint(2);
+        assert_eq!(status.last_paint_ms.load(std::sync::atomic::Ordering::Relaxed), first);
+    }
+}